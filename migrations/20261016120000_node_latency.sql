@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS node_latency (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    created TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    rpc_url VARCHAR(255),
+    reachable boolean,
+    connect_ms integer,
+    latency_ms integer,
+    is_synced boolean,
+    virtual_daa_score bigint
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS subnetwork_summary (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    date date,
+    subnetwork_id VARCHAR(40),
+    tx_qty integer,
+    UNIQUE (date, subnetwork_id)
+);
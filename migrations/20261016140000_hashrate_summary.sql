@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS hashrate_summary (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    timestamp TIMESTAMPTZ,
+    granularity VARCHAR(10),
+    block_count integer,
+    estimated_hashrate double precision,
+    rpc_hashrate double precision,
+    divergence double precision,
+    UNIQUE (timestamp, granularity)
+);
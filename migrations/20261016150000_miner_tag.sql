@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS miner_tag (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    pattern VARCHAR(255) UNIQUE,
+    pool_name VARCHAR(100),
+    created TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS miner_tag_unmatched (
+    tag VARCHAR(255) PRIMARY KEY,
+    first_seen date,
+    last_seen date,
+    block_count bigint
+);
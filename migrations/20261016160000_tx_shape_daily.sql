@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS tx_shape_daily (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    date date,
+    version integer,
+    tx_qty integer,
+    compute_mass_mean double precision,
+    compute_mass_median double precision,
+    compute_mass_max bigint,
+    storage_mass_mean double precision,
+    storage_mass_median double precision,
+    storage_mass_max bigint,
+    storage_compute_mass_ratio double precision,
+    near_limit_tx_qty integer,
+    UNIQUE (date, version)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS load_classification (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    timestamp TIMESTAMPTZ,
+    granularity VARCHAR(10),
+    regular_tx_qty integer,
+    payload_tx_qty integer,
+    repeated_payload_tx_qty integer,
+    inscription_tx_qty integer,
+    small_output_qty integer,
+    output_qty integer,
+    classification VARCHAR(20),
+    UNIQUE (timestamp, granularity)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS data_gap (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    start_time TIMESTAMPTZ,
+    end_time TIMESTAMPTZ,
+    reason VARCHAR(20),
+    pruning_point VARCHAR(64),
+    detected_at TIMESTAMPTZ DEFAULT now(),
+    UNIQUE (start_time, end_time, reason)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS pool_blocks (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    timestamp TIMESTAMPTZ,
+    granularity VARCHAR(10),
+    pool_name VARCHAR(100),
+    block_count integer,
+    block_share double precision,
+    estimated_hashrate double precision,
+    UNIQUE (timestamp, granularity, pool_name)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS rich_list (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    date date,
+    rank integer,
+    address VARCHAR(100),
+    balance bigint,
+    previous_rank integer,
+    UNIQUE (date, rank)
+);
+
+CREATE INDEX IF NOT EXISTS rich_list_address_date_idx ON rich_list (address, date);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS seconds_metrics (
+    timestamp TIMESTAMPTZ PRIMARY KEY,
+    block_count integer,
+    spc_block_count integer,
+    tx_count integer,
+    regular_tx_count integer,
+    input_count integer,
+    output_count integer,
+    fees_total bigint
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS annotations (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    start_time TIMESTAMPTZ,
+    end_time TIMESTAMPTZ,
+    label VARCHAR(100),
+    source VARCHAR(20),
+    note TEXT,
+    created TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (start_time, end_time, label, source)
+);
+
+CREATE INDEX IF NOT EXISTS annotations_time_range_idx ON annotations (start_time, end_time);
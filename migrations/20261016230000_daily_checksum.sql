@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS daily_checksum (
+    date date PRIMARY KEY,
+    spc_block_count bigint,
+    tx_count bigint,
+    fees_total bigint,
+    tx_id_xor CHAR(64),
+    computed_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
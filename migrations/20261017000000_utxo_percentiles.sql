@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS utxo_percentiles (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    date date,
+    percentile double precision,
+    address_count bigint,
+    balance_min bigint,
+    balance_max bigint,
+    balance_mean double precision,
+    balance_total bigint,
+    UNIQUE (date, percentile)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS consensus_split_event (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    kind VARCHAR(30),
+    reference_url VARCHAR(255),
+    node_url VARCHAR(255),
+    reference_daa_score bigint,
+    node_daa_score bigint,
+    detail TEXT,
+    detected_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS utxo_concentration (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    date date UNIQUE,
+    address_count bigint,
+    gini double precision,
+    nakamoto bigint,
+    herfindahl double precision
+);
@@ -0,0 +1,24 @@
+-- Contribution of each analysed day to network_totals, so re-analysing a day replaces its contribution
+CREATE TABLE IF NOT EXISTS network_totals_daily (
+    date date PRIMARY KEY,
+    block_count bigint,
+    spc_block_count bigint,
+    tx_count bigint,
+    fees_total bigint
+);
+
+-- Lifetime totals, single row
+CREATE TABLE IF NOT EXISTS network_totals (
+    id smallint PRIMARY KEY CHECK (id = 1),
+    block_count bigint NOT NULL,
+    spc_block_count bigint NOT NULL,
+    tx_count bigint NOT NULL,
+    fees_total bigint NOT NULL,
+    day_count bigint NOT NULL,
+    last_date date,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO network_totals (id, block_count, spc_block_count, tx_count, fees_total, day_count)
+VALUES (1, 0, 0, 0, 0, 0)
+ON CONFLICT (id) DO NOTHING;
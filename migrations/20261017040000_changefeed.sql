@@ -0,0 +1,9 @@
+-- Append-only log of committed writes, for incremental consumption by downstream ETL
+CREATE TABLE IF NOT EXISTS changefeed (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    entity_type VARCHAR(50) NOT NULL,
+    entity_id VARCHAR(100) NOT NULL,
+    op VARCHAR(20) NOT NULL,
+    batch_id BIGINT NOT NULL,
+    committed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
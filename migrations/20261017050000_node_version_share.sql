@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS node_version_share (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    timestamp TIMESTAMPTZ,
+    granularity VARCHAR(10),
+    node_version VARCHAR(100),
+    block_count bigint,
+    block_share double precision,
+    UNIQUE (timestamp, granularity, node_version)
+);
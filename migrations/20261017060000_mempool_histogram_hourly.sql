@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS mempool_histogram_hourly (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    hour TIMESTAMPTZ,
+    feerate_band double precision,
+    tx_count bigint,
+    mass_total bigint,
+    fees_total bigint,
+    snapshot_at TIMESTAMPTZ,
+    UNIQUE (hour, feerate_band)
+);
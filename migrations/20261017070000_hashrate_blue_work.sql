@@ -0,0 +1,4 @@
+ALTER TABLE hashrate_summary
+    ADD COLUMN IF NOT EXISTS difficulty double precision,
+    ADD COLUMN IF NOT EXISTS blue_work_hashrate double precision,
+    ADD COLUMN IF NOT EXISTS blue_work_divergence double precision;
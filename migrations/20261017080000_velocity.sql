@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS transfer_volume_daily (
+    date date PRIMARY KEY,
+    transfer_volume double precision
+);
+
+CREATE TABLE IF NOT EXISTS supply_history (
+    date date PRIMARY KEY,
+    circulating_supply bigint
+);
+
+CREATE TABLE IF NOT EXISTS velocity (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    period_start date,
+    period VARCHAR(10),
+    day_count integer,
+    transfer_volume double precision,
+    circulating_supply bigint,
+    velocity double precision,
+    UNIQUE (period_start, period)
+);
@@ -0,0 +1,3 @@
+-- difficulty held mean work per block, not difficulty
+ALTER TABLE hashrate_summary
+    RENAME COLUMN difficulty TO mean_block_work;
@@ -0,0 +1,13 @@
+-- Blocks per unmatched tag per day, so re-analysing a day replaces its count instead of adding to it
+CREATE TABLE IF NOT EXISTS miner_tag_unmatched_daily (
+    tag VARCHAR(255),
+    date date,
+    block_count bigint,
+    PRIMARY KEY (tag, date)
+);
+
+-- Existing counts can't be split by day, keep them as of the last day they were seen
+INSERT INTO miner_tag_unmatched_daily (tag, date, block_count)
+SELECT tag, last_seen, block_count
+FROM miner_tag_unmatched
+ON CONFLICT (tag, date) DO NOTHING;
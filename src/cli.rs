@@ -1,4 +1,6 @@
+use crate::utils::time::{parse_end_time, parse_time};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 pub struct Cli {
@@ -8,14 +10,189 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Commands {
+    #[command(flatten)]
+    Service(ServiceCommands),
+
+    #[command(flatten)]
+    Local(LocalCommands),
+}
+
+// Commands that run on local config and node data only, without RPC or PG
+#[derive(Subcommand)]
+pub enum LocalCommands {
+    /// Export transactions accepted inside a time window to CSV
+    ExportTransactions {
+        /// Export window start time. Unix milliseconds, RFC 3339 or YYYY-MM-DD
+        #[arg(value_parser = parse_time)]
+        start_time: u64,
+
+        /// Export window end time. Unix milliseconds, RFC 3339 or YYYY-MM-DD (end of that day). Window is limited to 7 days
+        #[arg(value_parser = parse_end_time)]
+        end_time: u64,
+
+        /// Output CSV file path
+        output: PathBuf,
+    },
+
+    /// Validate and normalize addresses against the configured network
+    ValidateAddresses {
+        /// Addresses, with or without network prefix
+        #[arg(required = true)]
+        addresses: Vec<String>,
+    },
+
+    /// Inspect application configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+// Commands that need the RPC node and PG
+#[derive(Subcommand)]
+pub enum ServiceCommands {
+    /// Analyse yesterday, or backfill a time window one day at a time
+    /// Every UTC day touched by the window is analysed as a whole day, up to yesterday
     Analysis {
-        /// Analysis window start time, in unix milliseconds
+        /// Backfill window start time. Unix milliseconds, RFC 3339 or YYYY-MM-DD. Analyses yesterday when omitted
+        #[arg(value_parser = parse_time)]
         start_time: Option<u64>,
 
-        /// Analysis window end time, in unix milliseconds
+        /// Backfill window end time. Unix milliseconds, RFC 3339 or YYYY-MM-DD (end of that day). Defaults to end of yesterday
+        #[arg(value_parser = parse_end_time)]
         end_time: Option<u64>,
     },
 
+    /// Recompute daily checksums from node data and compare them against stored checksums
+    /// Every UTC day touched by the window is verified as a whole day
+    VerifyChecksums {
+        /// Verification window start time. Unix milliseconds, RFC 3339 or YYYY-MM-DD
+        #[arg(value_parser = parse_time)]
+        start_time: u64,
+
+        /// Verification window end time. Unix milliseconds, RFC 3339 or YYYY-MM-DD (end of that day)
+        #[arg(value_parser = parse_end_time)]
+        end_time: u64,
+
+        /// Postgres connection string of a peer instance whose checksums are also compared
+        #[arg(long)]
+        peer_db_uri: Option<String>,
+    },
+
+    /// Save a histogram of the mempool by feerate band for the current hour, meant to run hourly
+    SnapshotMempool,
+
+    /// Compare chain state of RPC_URL and PROBE_RPC_URLS nodes and alert on divergence
+    MonitorConsensus,
+
+    /// Report balance, UTXOs and rich list history of an address
+    AddressReport {
+        /// Address, with or without network prefix
+        address: String,
+
+        /// Print the report as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Measure RPC latency of the public nodes listed in PROBE_RPC_URLS and save results
+    ProbeNodes,
+
+    /// Print changefeed entries after a cursor, one per line:
+    /// id, batch id, committed at, entity type, entity id, op
+    Changefeed {
+        /// Id of the last entry already consumed
+        #[arg(long, default_value_t = 0)]
+        after: i64,
+
+        /// Maximum number of entries to print
+        #[arg(long, default_value_t = 1000)]
+        limit: i64,
+    },
+
+    /// Print lifetime network totals, followed by the most recent daily contributions, one per line:
+    /// date, blocks, spc blocks, txs, fees
+    NetworkTotals {
+        /// Number of most recent days to print
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+    },
+
+    /// Manage annotations attached to time ranges, rendered as event markers on charts
+    Annotations {
+        #[command(subcommand)]
+        command: AnnotationCommands,
+    },
+
     /// Reset database (drop entire database and recreate). Can only be used in dev env.
     ResetDb,
 }
+
+impl Commands {
+    // Start and end time arguments of the command, when it has both
+    // Export windows are checked by export::check_window instead
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        match self {
+            Commands::Service(ServiceCommands::Analysis {
+                start_time: Some(start_time),
+                end_time: Some(end_time),
+            })
+            | Commands::Service(ServiceCommands::VerifyChecksums {
+                start_time,
+                end_time,
+                ..
+            })
+            | Commands::Service(ServiceCommands::Annotations {
+                command:
+                    AnnotationCommands::Add {
+                        start_time,
+                        end_time,
+                        ..
+                    }
+                    | AnnotationCommands::List {
+                        start_time,
+                        end_time,
+                    },
+            }) => Some((*start_time, *end_time)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print resolved configuration, including defaults, with secrets redacted
+    PrintEffective,
+}
+
+#[derive(Subcommand)]
+pub enum AnnotationCommands {
+    /// Annotate a time range
+    Add {
+        /// Annotated range start time. Unix milliseconds, RFC 3339 or YYYY-MM-DD
+        #[arg(value_parser = parse_time)]
+        start_time: u64,
+
+        /// Annotated range end time. Unix milliseconds, RFC 3339 or YYYY-MM-DD (end of that day)
+        #[arg(value_parser = parse_end_time)]
+        end_time: u64,
+
+        /// Short label, i.e. "hardfork activation"
+        label: String,
+
+        /// Optional free text note
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Print annotations overlapping a time range
+    List {
+        /// Range start time. Unix milliseconds, RFC 3339 or YYYY-MM-DD
+        #[arg(value_parser = parse_time)]
+        start_time: u64,
+
+        /// Range end time. Unix milliseconds, RFC 3339 or YYYY-MM-DD (end of that day)
+        #[arg(value_parser = parse_end_time)]
+        end_time: u64,
+    },
+}
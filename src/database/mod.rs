@@ -1,7 +1,7 @@
 mod pg;
 pub mod initialize;
 
-pub use pg::Database;
+pub use pg::{classify_error, Database};
 
 use strum_macros::{Display, EnumIter};
 
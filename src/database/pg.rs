@@ -1,3 +1,4 @@
+use crate::utils::retry::Retry;
 use log::info;
 use sqlx::{
     postgres::{PgPool, PgPoolOptions},
@@ -59,3 +60,13 @@ impl Database {
         Ok(())
     }
 }
+
+// Connection level failures are retried, query errors (constraint violations, bad SQL, ...) are not
+pub fn classify_error(e: &sqlx::Error) -> Retry {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => {
+            Retry::Transient
+        }
+        _ => Retry::Permanent,
+    }
+}
@@ -0,0 +1,226 @@
+use kaspa_consensus_core::tx::{ScriptPublicKey, ScriptVec};
+use std::fmt;
+
+const LENGTH_OF_BLUE_SCORE: usize = 8;
+const LENGTH_OF_SUBSIDY: usize = 8;
+const LENGTH_OF_SCRIPT_PUB_KEY_VERSION: usize = 2;
+const LENGTH_OF_SCRIPT_PUB_KEY_LENGTH: usize = 1;
+const MIN_PAYLOAD_LENGTH: usize = LENGTH_OF_BLUE_SCORE
+    + LENGTH_OF_SUBSIDY
+    + LENGTH_OF_SCRIPT_PUB_KEY_VERSION
+    + LENGTH_OF_SCRIPT_PUB_KEY_LENGTH;
+
+// Longest miner tag kept, longer tags are truncated
+const MAX_MINER_TAG_LENGTH: usize = 255;
+
+// Longest node version kept, longer versions are truncated
+const MAX_NODE_VERSION_LENGTH: usize = 100;
+
+#[derive(Debug)]
+pub enum CoinbasePayloadError {
+    TooShort(usize),
+    ScriptPublicKeyTruncated(usize),
+    ExtraDataNotUtf8,
+}
+
+impl fmt::Display for CoinbasePayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoinbasePayloadError::TooShort(len) => {
+                write!(
+                    f,
+                    "payload length {} below minimum {}",
+                    len, MIN_PAYLOAD_LENGTH
+                )
+            }
+            CoinbasePayloadError::ScriptPublicKeyTruncated(len) => {
+                write!(f, "payload length {} cannot contain script public key", len)
+            }
+            CoinbasePayloadError::ExtraDataNotUtf8 => write!(f, "extra data is not valid utf-8"),
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub struct CoinbasePayload {
+    pub blue_score: u64,
+    pub subsidy: u64,
+    pub script_public_key: ScriptPublicKey,
+    pub extra_data: String,
+}
+
+impl CoinbasePayload {
+    // Parses a coinbase transaction payload, never panics on malformed input
+    // In strict mode, extra data that is not valid UTF-8 is an error. Otherwise it is decoded lossily.
+    pub fn parse(payload: &[u8], strict: bool) -> Result<Self, CoinbasePayloadError> {
+        if payload.len() < MIN_PAYLOAD_LENGTH {
+            return Err(CoinbasePayloadError::TooShort(payload.len()));
+        }
+
+        let (blue_score, rest) = payload.split_at(LENGTH_OF_BLUE_SCORE);
+        let (subsidy, rest) = rest.split_at(LENGTH_OF_SUBSIDY);
+        let (script_pub_key_version, rest) = rest.split_at(LENGTH_OF_SCRIPT_PUB_KEY_VERSION);
+        let (script_pub_key_len, rest) = rest.split_at(LENGTH_OF_SCRIPT_PUB_KEY_LENGTH);
+
+        let script_pub_key_len = script_pub_key_len[0] as usize;
+        if rest.len() < script_pub_key_len {
+            return Err(CoinbasePayloadError::ScriptPublicKeyTruncated(
+                payload.len(),
+            ));
+        }
+        let (script, extra_data) = rest.split_at(script_pub_key_len);
+
+        let extra_data = if strict {
+            String::from_utf8(extra_data.to_vec())
+                .map_err(|_| CoinbasePayloadError::ExtraDataNotUtf8)?
+        } else {
+            String::from_utf8_lossy(extra_data).into_owned()
+        };
+
+        Ok(Self {
+            blue_score: u64::from_le_bytes(blue_score.try_into().unwrap()),
+            subsidy: u64::from_le_bytes(subsidy.try_into().unwrap()),
+            script_public_key: ScriptPublicKey::new(
+                u16::from_le_bytes(script_pub_key_version.try_into().unwrap()),
+                ScriptVec::from_slice(script),
+            ),
+            extra_data,
+        })
+    }
+
+    // Extra data is by convention "<node version>/<miner tag>"
+    pub fn node_version(&self) -> Option<&str> {
+        let version = match self.extra_data.split_once('/') {
+            Some((version, _)) => version,
+            None => self.extra_data.as_str(),
+        }
+        .trim();
+
+        if !version.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        match version.char_indices().nth(MAX_NODE_VERSION_LENGTH) {
+            Some((end, _)) => Some(&version[..end]),
+            None => Some(version),
+        }
+    }
+
+    pub fn miner_tag(&self) -> Option<String> {
+        let (_, tag) = self.extra_data.split_once('/')?;
+        let tag = tag.trim_matches(|c: char| c.is_whitespace() || c.is_control());
+
+        if tag.is_empty() {
+            return None;
+        }
+
+        Some(tag.chars().take(MAX_MINER_TAG_LENGTH).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CoinbasePayload, CoinbasePayloadError, MAX_MINER_TAG_LENGTH, MAX_NODE_VERSION_LENGTH,
+        MIN_PAYLOAD_LENGTH,
+    };
+
+    // Builds a payload with a 34 byte script public key followed by `extra_data`
+    fn payload(extra_data: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend(42u64.to_le_bytes());
+        payload.extend(50_000_000u64.to_le_bytes());
+        payload.extend(0u16.to_le_bytes());
+        payload.push(34);
+        payload.extend([0xaa; 34]);
+        payload.extend(extra_data);
+        payload
+    }
+
+    #[test]
+    fn parses_header_and_extra_data() {
+        let parsed = CoinbasePayload::parse(&payload(b"0.14.1/my-pool"), true).unwrap();
+
+        assert_eq!(parsed.blue_score, 42);
+        assert_eq!(parsed.subsidy, 50_000_000);
+        assert_eq!(parsed.script_public_key.version(), 0);
+        assert_eq!(parsed.script_public_key.script(), &[0xaa; 34]);
+        assert_eq!(parsed.node_version(), Some("0.14.1"));
+        assert_eq!(parsed.miner_tag().as_deref(), Some("my-pool"));
+    }
+
+    #[test]
+    fn empty_payload_is_too_short() {
+        assert!(matches!(
+            CoinbasePayload::parse(&[], true),
+            Err(CoinbasePayloadError::TooShort(0))
+        ));
+    }
+
+    #[test]
+    fn truncated_header_is_too_short() {
+        let payload = payload(b"");
+        assert!(matches!(
+            CoinbasePayload::parse(&payload[..MIN_PAYLOAD_LENGTH - 1], false),
+            Err(CoinbasePayloadError::TooShort(len)) if len == MIN_PAYLOAD_LENGTH - 1
+        ));
+    }
+
+    #[test]
+    fn short_script_is_truncated() {
+        let payload = payload(b"");
+        assert!(matches!(
+            CoinbasePayload::parse(&payload[..payload.len() - 1], false),
+            Err(CoinbasePayloadError::ScriptPublicKeyTruncated(len)) if len == payload.len() - 1
+        ));
+    }
+
+    #[test]
+    fn non_utf8_extra_data() {
+        let payload = payload(b"0.14.1/pool-\xff\xfe");
+
+        assert!(matches!(
+            CoinbasePayload::parse(&payload, true),
+            Err(CoinbasePayloadError::ExtraDataNotUtf8)
+        ));
+
+        let parsed = CoinbasePayload::parse(&payload, false).unwrap();
+        assert_eq!(parsed.node_version(), Some("0.14.1"));
+        assert_eq!(parsed.miner_tag().as_deref(), Some("pool-\u{fffd}\u{fffd}"));
+    }
+
+    #[test]
+    fn long_miner_tag_is_truncated() {
+        let tag = "x".repeat(MAX_MINER_TAG_LENGTH + 45);
+        let parsed =
+            CoinbasePayload::parse(&payload(format!("0.14.1/{}", tag).as_bytes()), true).unwrap();
+
+        assert_eq!(
+            parsed.miner_tag().unwrap().chars().count(),
+            MAX_MINER_TAG_LENGTH
+        );
+    }
+
+    #[test]
+    fn long_node_version_is_truncated() {
+        let version = format!("0.14.1{}", "é".repeat(MAX_NODE_VERSION_LENGTH));
+        let parsed =
+            CoinbasePayload::parse(&payload(format!("{}/my-pool", version).as_bytes()), true)
+                .unwrap();
+
+        let node_version = parsed.node_version().unwrap();
+        assert_eq!(node_version.chars().count(), MAX_NODE_VERSION_LENGTH);
+        assert!(version.starts_with(node_version));
+    }
+
+    #[test]
+    fn missing_version_and_tag() {
+        let parsed = CoinbasePayload::parse(&payload(b""), true).unwrap();
+        assert_eq!(parsed.node_version(), None);
+        assert_eq!(parsed.miner_tag(), None);
+
+        let parsed = CoinbasePayload::parse(&payload(b"my-pool/ \n"), true).unwrap();
+        assert_eq!(parsed.node_version(), None);
+        assert_eq!(parsed.miner_tag(), None);
+    }
+}
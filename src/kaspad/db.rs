@@ -1,7 +1,14 @@
 use kaspa_consensus::consensus::{
     factory::MultiConsensusManagementStore, storage::ConsensusStorage,
 };
-use kaspa_consensus_core::{config::ConfigBuilder, network::NetworkId};
+use kaspa_consensus::model::stores::headers::HeaderStoreReader;
+use kaspa_consensus::model::stores::pruning::PruningStoreReader;
+use kaspa_consensus::model::stores::utxo_diffs::UtxoDiffsStoreReader;
+use kaspa_consensus_core::tx::{TransactionOutpoint, UtxoEntry};
+use kaspa_consensus_core::utxo::utxo_diff::ImmutableUtxoDiff;
+use kaspa_consensus_core::{config::ConfigBuilder, network::NetworkId, Hash};
+use kaspa_database::prelude::StoreError;
+use std::collections::{BTreeMap, HashMap};
 use std::{path::{Path, PathBuf}, str::FromStr, sync::Arc};
 
 pub fn get_active_consensus_dir(meta_db_dir: PathBuf) -> PathBuf {
@@ -33,3 +40,58 @@ pub fn init_consensus_storage(
 
     ConsensusStorage::new(db, config)
 }
+
+// Loads chain blocks with a timestamp inside [start_time, end_time], keyed by selected chain index
+pub fn get_chain_blocks_in_window(
+    storage: &ConsensusStorage,
+    start_time: u64,
+    end_time: u64,
+) -> BTreeMap<u64, Hash> {
+    let mut chain_blocks = BTreeMap::<u64, Hash>::new();
+
+    for (key, hash) in storage
+        .selected_chain_store
+        .read()
+        .access_hash_by_index
+        .iterator()
+        .map(|p| p.unwrap())
+    {
+        let key = u64::from_le_bytes((*key).try_into().unwrap());
+        let header = storage.headers_store.get_header(hash).unwrap();
+
+        if start_time <= header.timestamp && header.timestamp <= end_time {
+            chain_blocks.insert(key, hash);
+        }
+    }
+
+    chain_blocks
+}
+
+// Reads utxo_diffs_store for given chain block
+// Returns a single map of all UTXOs affected (created or removed) for chain block
+pub fn get_utxos_for_chain_block(
+    storage: &ConsensusStorage,
+    hash: Hash,
+) -> Result<HashMap<TransactionOutpoint, UtxoEntry>, StoreError> {
+    let utxo_diffs = storage.utxo_diffs_store.get(hash)?;
+    let mut utxos = HashMap::<TransactionOutpoint, UtxoEntry>::new();
+
+    utxo_diffs.removed().iter().for_each(|(outpoint, utxo)| {
+        utxos.insert(*outpoint, utxo.clone());
+    });
+
+    utxo_diffs.added().iter().for_each(|(outpoint, utxo)| {
+        utxos.insert(*outpoint, utxo.clone());
+    });
+
+    Ok(utxos)
+}
+
+// Current pruning point of the node and its timestamp
+// Block data older than the pruning point is deleted by the node
+pub fn get_pruning_point(storage: &ConsensusStorage) -> Result<(Hash, u64), StoreError> {
+    let pruning_point = storage.pruning_point_store.read().pruning_point()?;
+    let header = storage.headers_store.get_header(pruning_point)?;
+
+    Ok((pruning_point, header.timestamp))
+}
@@ -1,2 +1,4 @@
+pub mod coinbase;
 pub mod db;
 pub mod dirs;
+pub mod rpc;
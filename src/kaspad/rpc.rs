@@ -0,0 +1,83 @@
+use crate::utils::config::Config;
+use crate::utils::retry::{retry, Backoff, Retry};
+use kaspa_rpc_core::api::rpc::RpcApi;
+use kaspa_wrpc_client::client::{ConnectOptions, ConnectStrategy};
+use kaspa_wrpc_client::{KaspaRpcClient, WrpcEncoding};
+use log::warn;
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Connects to the first healthy node of RPC_URL followed by RPC_FALLBACK_URLS
+// Once connected, the client reconnects to the same node on its own if the connection drops
+pub async fn connect(config: &Config) -> KaspaRpcClient {
+    try_connect(config)
+        .await
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Same as connect, but returns an error instead of panicking when no node is healthy
+pub async fn try_connect(config: &Config) -> Result<KaspaRpcClient, String> {
+    for url in config.rpc.urls() {
+        match connect_to(config, url).await {
+            Ok(rpc_client) => {
+                if *url != config.rpc.url {
+                    warn!("Failed over to RPC node {}", url);
+                }
+                return Ok(rpc_client);
+            }
+            Err(e) => warn!("RPC node {} unavailable: {}", url, e),
+        }
+    }
+
+    Err(String::from(
+        "No healthy RPC node in RPC_URL or RPC_FALLBACK_URLS",
+    ))
+}
+
+// Connects to a single node and checks it is synced and on the configured network
+pub async fn connect_to(config: &Config, url: &str) -> Result<KaspaRpcClient, String> {
+    let rpc_client = KaspaRpcClient::new(
+        WrpcEncoding::Borsh,
+        Some(url),
+        None,
+        Some(config.network_id),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Fail each attempt after CONNECT_TIMEOUT instead of letting the client retry forever
+    let options = ConnectOptions {
+        block_async_connect: true,
+        strategy: ConnectStrategy::Fallback,
+        connect_timeout: Some(CONNECT_TIMEOUT),
+        ..Default::default()
+    };
+    let backoff =
+        Backoff::new(Duration::from_secs(1), Duration::from_secs(30)).with_max_attempts(3);
+
+    retry(
+        "KaspaRpcClient::connect",
+        &backoff,
+        || rpc_client.connect(Some(options.clone())),
+        |_| Retry::Transient,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let health = match rpc_client.get_server_info().await {
+        Ok(server_info) if !server_info.is_synced => Err(String::from("node is not synced")),
+        Ok(server_info) if server_info.network_id.network_type != *config.network_id => {
+            Err(format!("node is on {}", server_info.network_id))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    if let Err(e) = health {
+        let _ = rpc_client.disconnect().await;
+        return Err(e);
+    }
+
+    Ok(rpc_client)
+}
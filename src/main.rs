@@ -6,12 +6,12 @@ mod service;
 mod utils;
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ConfigCommands, LocalCommands, ServiceCommands};
 use env_logger::{Builder, Env};
 use kaspa_rpc_core::api::rpc::RpcApi;
-use kaspa_wrpc_client::{KaspaRpcClient, WrpcEncoding};
 use log::{info, LevelFilter};
 use service::analysis::Analysis;
+use service::export::TransactionExport;
 use std::io;
 use utils::config::Config;
 
@@ -23,16 +23,7 @@ fn prompt_confirmation(prompt: &str) -> bool {
 }
 
 async fn check_rpc_node_status(config: &Config) {
-    let rpc_client = KaspaRpcClient::new(
-        WrpcEncoding::Borsh,
-        Some(&config.rpc_url),
-        None,
-        Some(config.network_id),
-        None,
-    )
-    .unwrap();
-
-    rpc_client.connect(None).await.unwrap();
+    let rpc_client = kaspad::rpc::connect(config).await;
 
     let server_info = rpc_client.get_server_info().await.unwrap();
 
@@ -49,10 +40,47 @@ async fn check_rpc_node_status(config: &Config) {
     }
 }
 
+// Runs commands that need neither RPC nor PG
+fn run_local(config: Config, command: LocalCommands) {
+    match command {
+        LocalCommands::Config {
+            command: ConfigCommands::PrintEffective,
+        } => println!("{}", config),
+        LocalCommands::ValidateAddresses { addresses } => {
+            let mut all_valid = true;
+            for input in addresses.iter() {
+                match utils::address::parse_address(input, config.network_id) {
+                    Ok(address) => println!("{}\tvalid\t{}", input, address),
+                    Err(e) => {
+                        println!("{}\tinvalid\t{}", input, e);
+                        all_valid = false;
+                    }
+                }
+            }
+
+            if !all_valid {
+                std::process::exit(1);
+            }
+        }
+        LocalCommands::ExportTransactions {
+            start_time,
+            end_time,
+            output,
+        } => {
+            if let Err(e) = service::export::check_window(start_time, end_time) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+
+            TransactionExport::main(config, start_time, end_time, &output);
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     // Load config from .env file
-    let config = crate::utils::config::Config::from_env();
+    let config = crate::utils::config::Config::from_env().unwrap_or_else(|e| panic!("{}", e));
 
     // Init Logger
     Builder::from_env(Env::default().default_filter_or("info"))
@@ -63,6 +91,19 @@ async fn main() {
     // Parse CLI command and args
     let cli = Cli::parse();
 
+    if let Some((start_time, end_time)) = cli.command.time_range() {
+        if let Err(e) = utils::time::check_range(start_time, end_time) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Local commands run before touching RPC or PG
+    let command = match cli.command {
+        Commands::Service(command) => command,
+        Commands::Local(command) => return run_local(config, command),
+    };
+
     // Ensure node is synced, is same network/suffix as supplied CLI args, is utxoindexed
     // This check is done via RPC
     // WARNING:
@@ -72,8 +113,11 @@ async fn main() {
     check_rpc_node_status(&config).await;
 
     // Get PG connection pool
-    let db = database::Database::new(config.db_uri.clone());
-    let db_pool = db.open_connection_pool(5u32).await.unwrap();
+    let db = database::Database::new(config.db.uri.clone());
+    let db_pool = db
+        .open_connection_pool(config.db.max_connections)
+        .await
+        .unwrap();
 
     // Apply PG migrations and insert static records
     database::initialize::apply_migrations(&db_pool)
@@ -99,12 +143,36 @@ async fn main() {
     }
 
     // Run submitted CLI command
-    match cli.command {
-        Commands::Analysis {
-            start_time: _,
-            end_time: _,
-        } => Analysis::main(config, &db_pool).await, // TODO support start_time and end_time
-        Commands::ResetDb => {
+    match command {
+        ServiceCommands::Analysis {
+            start_time,
+            end_time,
+        } => Analysis::main(config, &db_pool, start_time, end_time).await,
+        ServiceCommands::VerifyChecksums {
+            start_time,
+            end_time,
+            peer_db_uri,
+        } => service::checksum::verify(&config, &db_pool, start_time, end_time, peer_db_uri).await,
+        ServiceCommands::ProbeNodes => service::probe::run(&config, &db_pool).await,
+        ServiceCommands::AddressReport { address, json } => {
+            match utils::address::parse_address(&address, config.network_id) {
+                Ok(address) => service::address_report::run(&config, &db_pool, address, json).await,
+                Err(e) => {
+                    eprintln!("{}: {}", address, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        ServiceCommands::SnapshotMempool => service::mempool::run(&config, &db_pool).await,
+        ServiceCommands::MonitorConsensus => service::consensus::monitor(&config, &db_pool).await,
+        ServiceCommands::Changefeed { after, limit } => {
+            service::changefeed::run(&db_pool, after, limit).await
+        }
+        ServiceCommands::NetworkTotals { days } => service::totals::run(&db_pool, days).await,
+        ServiceCommands::Annotations { command } => {
+            service::annotations::run(&db_pool, command).await
+        }
+        ServiceCommands::ResetDb => {
             if config.env == utils::config::Env::Prod {
                 panic!("Cannot use --reset-db in production.")
             }
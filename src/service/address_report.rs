@@ -0,0 +1,160 @@
+use crate::utils::config::Config;
+use chrono::NaiveDate;
+use kaspa_addresses::Address;
+use kaspa_rpc_core::api::rpc::RpcApi;
+use serde_json::json;
+use sqlx::PgPool;
+
+// Rich list history of an address
+struct RichListHistory {
+    first_date: NaiveDate,
+    latest_date: NaiveDate,
+    latest_rank: i32,
+    latest_balance: i64,
+    best_rank: i32,
+}
+
+impl RichListHistory {
+    async fn load(pool: &PgPool, address: &Address) -> Result<Option<Self>, sqlx::Error> {
+        let sql = r#"
+            SELECT
+                MIN(date),
+                MAX(date),
+                (ARRAY_AGG(rank ORDER BY date DESC))[1],
+                (ARRAY_AGG(balance ORDER BY date DESC))[1],
+                MIN(rank)
+            FROM rich_list
+            WHERE address = $1
+            HAVING COUNT(*) > 0
+        "#;
+
+        let row: Option<(NaiveDate, NaiveDate, i32, i64, i32)> = sqlx::query_as(sql)
+            .bind(address.to_string())
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(
+            |(first_date, latest_date, latest_rank, latest_balance, best_rank)| Self {
+                first_date,
+                latest_date,
+                latest_rank,
+                latest_balance,
+                best_rank,
+            },
+        ))
+    }
+}
+
+// Current state of an address from the node's UTXO index, plus its rich list history from PG
+// Transactions are not persisted, so sent/received totals and counterparties are not available
+pub struct AddressReport {
+    address: Address,
+    virtual_daa_score: u64,
+    balance: u64,
+    utxo_count: usize,
+    coinbase_utxo_count: usize,
+    oldest_utxo_daa_score: Option<u64>,
+    newest_utxo_daa_score: Option<u64>,
+    rich_list: Option<RichListHistory>,
+}
+
+impl AddressReport {
+    pub async fn new(config: &Config, pool: &PgPool, address: Address) -> Self {
+        let rpc_client = crate::kaspad::rpc::connect(config).await;
+
+        let virtual_daa_score = rpc_client
+            .get_block_dag_info()
+            .await
+            .unwrap()
+            .virtual_daa_score;
+        let utxos = rpc_client
+            .get_utxos_by_addresses(vec![address.clone()])
+            .await
+            .unwrap();
+
+        rpc_client.disconnect().await.unwrap();
+
+        let daa_scores = utxos.iter().map(|utxo| utxo.utxo_entry.block_daa_score);
+
+        Self {
+            virtual_daa_score,
+            balance: utxos.iter().map(|utxo| utxo.utxo_entry.amount).sum(),
+            utxo_count: utxos.len(),
+            coinbase_utxo_count: utxos
+                .iter()
+                .filter(|utxo| utxo.utxo_entry.is_coinbase)
+                .count(),
+            oldest_utxo_daa_score: daa_scores.clone().min(),
+            newest_utxo_daa_score: daa_scores.max(),
+            rich_list: RichListHistory::load(pool, &address).await.unwrap(),
+            address,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "address": self.address.to_string(),
+            "virtual_daa_score": self.virtual_daa_score,
+            "balance": self.balance,
+            "utxo_count": self.utxo_count,
+            "coinbase_utxo_count": self.coinbase_utxo_count,
+            "oldest_utxo_daa_score": self.oldest_utxo_daa_score,
+            "newest_utxo_daa_score": self.newest_utxo_daa_score,
+            "rich_list": self.rich_list.as_ref().map(|history| json!({
+                "first_date": history.first_date.to_string(),
+                "latest_date": history.latest_date.to_string(),
+                "latest_rank": history.latest_rank,
+                "latest_balance": history.latest_balance,
+                "best_rank": history.best_rank,
+            })),
+        })
+    }
+
+    pub fn to_table(&self) -> String {
+        let optional = |value: Option<u64>| value.map_or(String::from("-"), |v| v.to_string());
+
+        let mut rows = vec![
+            ("Address", self.address.to_string()),
+            ("Virtual DAA score", self.virtual_daa_score.to_string()),
+            ("Balance (sompi)", self.balance.to_string()),
+            ("UTXOs", self.utxo_count.to_string()),
+            ("Coinbase UTXOs", self.coinbase_utxo_count.to_string()),
+            (
+                "Oldest UTXO DAA score",
+                optional(self.oldest_utxo_daa_score),
+            ),
+            (
+                "Newest UTXO DAA score",
+                optional(self.newest_utxo_daa_score),
+            ),
+        ];
+
+        match &self.rich_list {
+            Some(history) => rows.extend([
+                ("Rich list first seen", history.first_date.to_string()),
+                ("Rich list latest", history.latest_date.to_string()),
+                ("Rich list latest rank", history.latest_rank.to_string()),
+                (
+                    "Rich list latest balance",
+                    history.latest_balance.to_string(),
+                ),
+                ("Rich list best rank", history.best_rank.to_string()),
+            ]),
+            None => rows.push(("Rich list", String::from("never ranked"))),
+        }
+
+        rows.iter()
+            .map(|(label, value)| format!("{:<26}{}", label, value))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+pub async fn run(config: &Config, pool: &PgPool, address: Address, json: bool) {
+    let report = AddressReport::new(config, pool, address).await;
+
+    match json {
+        true => println!("{}", report.to_json()),
+        false => println!("{}", report.to_table()),
+    }
+}
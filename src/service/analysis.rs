@@ -1,62 +1,108 @@
-use crate::service::stats::Stats;
-use crate::utils::config::Config;
+use crate::database::classify_error;
+use crate::kaspad::coinbase::CoinbasePayload;
+use crate::service::annotations::{Annotation, AnnotationSource};
+use crate::service::anomaly::{self, Anomaly, AnomalyMetric};
+use crate::service::checksum::DailyChecksum;
+use crate::service::hashrate::{self, HashrateEstimate, RPC_ESTIMATE_WINDOW_SIZE};
+use crate::service::load::{self, LoadClass, LoadClassification};
+use crate::service::miners::MinerTagRegistry;
+use crate::service::stats::{Counter, Stats};
+use crate::service::totals::NetworkTotals;
+use crate::service::utxo::UtxoAnalysis;
+use crate::service::{velocity, versions};
+use crate::utils::config::{AnalysisModule, Config};
+use chrono::DateTime;
+use kaspa_addresses::Address;
 use kaspa_consensus::consensus::storage::ConsensusStorage;
 use kaspa_consensus::model::stores::acceptance_data::AcceptanceDataStoreReader;
 use kaspa_consensus::model::stores::block_transactions::BlockTransactionsStoreReader;
 use kaspa_consensus::model::stores::headers::HeaderStoreReader;
 use kaspa_consensus::model::stores::selected_chain::SelectedChainStoreReader;
-use kaspa_consensus::model::stores::utxo_diffs::UtxoDiffsStoreReader;
-use kaspa_consensus_core::tx::{TransactionId, TransactionOutpoint, UtxoEntry};
-use kaspa_consensus_core::utxo::utxo_diff::ImmutableUtxoDiff;
+use kaspa_consensus::processes::difficulty::calc_work;
+use kaspa_consensus::processes::mass::MassCalculator;
+use kaspa_consensus_core::config::params::Params;
+use kaspa_consensus_core::tx::{PopulatedTransaction, TransactionId};
 use kaspa_consensus_core::Hash;
 use kaspa_database::prelude::StoreError;
+use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_txscript::standard::extract_script_pub_key_address;
-use log::{error, info};
+use log::{error, info, warn};
 use sqlx::PgPool;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::time::sleep;
+use std::time::Duration;
 
-use super::Granularity;
+use crate::utils::granularity::{Aggregatable, Granularity};
+use crate::utils::retry::{retry, Backoff, Retry};
+
+// Number of most recent chain blocks whose transactions are kept in the dedup cache
+const DEDUP_WINDOW: usize = 2700;
 
 pub struct Analysis {
     config: Config,
     storage: Arc<ConsensusStorage>,
+    mass_calculator: MassCalculator,
     window_start_time: u64,
     window_end_time: u64,
     chain_blocks: BTreeMap<u64, Hash>,
     stats: BTreeMap<u64, Stats>,
 }
 
-impl Analysis {
-    pub fn new_for_yesterday(config: Config, storage: Arc<ConsensusStorage>) -> Self {
-        let start_of_today = chrono::Utc::now()
-            .date_naive()
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
-        let start_of_yesterday = start_of_today - chrono::Duration::days(1);
-        let end_of_yesterday = start_of_today - chrono::Duration::milliseconds(1);
+// Backoff of PG saves, transient connection errors are retried for up to ~5 minutes
+fn save_backoff() -> Backoff {
+    Backoff::new(Duration::from_secs(1), Duration::from_secs(60))
+        .with_max_elapsed(Duration::from_secs(5 * 60))
+}
 
-        Self {
-            config,
-            storage,
-            window_start_time: start_of_yesterday.and_utc().timestamp_millis() as u64,
-            window_end_time: end_of_yesterday.and_utc().timestamp_millis() as u64,
-            chain_blocks: BTreeMap::<u64, Hash>::new(),
-            stats: BTreeMap::<u64, Stats>::new(),
-        }
+// Start and end of yesterday (UTC), in unix milliseconds
+fn yesterday_window() -> (u64, u64) {
+    let start_of_today = chrono::Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let start_of_yesterday = start_of_today - chrono::Duration::days(1);
+    let end_of_yesterday = start_of_today - chrono::Duration::milliseconds(1);
+
+    (
+        start_of_yesterday.and_utc().timestamp_millis() as u64,
+        end_of_yesterday.and_utc().timestamp_millis() as u64,
+    )
+}
+
+// Whole UTC days touched by [start_time, end_time], as (start, end) in unix milliseconds
+// Windows are never clipped to start_time or end_time, as daily stats are saved per whole day
+pub fn daily_windows(start_time: u64, end_time: u64) -> Vec<(u64, u64)> {
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    let mut windows = Vec::<(u64, u64)>::new();
+    let mut window_start_time = start_time / DAY_MS * DAY_MS;
+    while window_start_time <= end_time {
+        windows.push((window_start_time, window_start_time + DAY_MS - 1));
+        window_start_time += DAY_MS;
     }
 
-    #[allow(dead_code)]
+    windows
+}
+
+impl Analysis {
     pub fn new_from_time_window(
         config: Config,
         storage: Arc<ConsensusStorage>,
         start_time: u64,
         end_time: u64,
     ) -> Self {
+        let params: Params = config.network_id.into();
+        let mass_calculator = MassCalculator::new(
+            params.mass_per_tx_byte,
+            params.mass_per_script_pub_key_byte,
+            params.mass_per_sig_op,
+            params.storage_mass_parameter,
+        );
+
         Self {
             config,
             storage,
+            mass_calculator,
             window_start_time: start_time,
             window_end_time: end_time,
             chain_blocks: BTreeMap::<u64, Hash>::new(),
@@ -87,217 +133,368 @@ impl Analysis {
 }
 
 impl Analysis {
-    fn load_chain_blocks(&mut self) {
-        for (key, hash) in self
-            .storage
-            .selected_chain_store
-            .read()
-            .access_hash_by_index
-            .iterator()
-            .map(|p| p.unwrap())
-        {
-            let key = u64::from_le_bytes((*key).try_into().unwrap());
-            let header = self.storage.headers_store.get_header(hash).unwrap();
-
-            if self.window_start_time <= header.timestamp
-                && header.timestamp <= self.window_end_time
-            {
-                self.chain_blocks.insert(key, hash);
-            }
+    // Detects when the node has pruned part of the target window
+    // Daily stats are only saved for whole days, so a window the pruning point falls in is skipped
+    // as a whole, rather than saving hourly data of its surviving part without the daily summaries
+    // The skipped window is recorded in data_gap and operators are alerted
+    // Returns false when the window is skipped
+    async fn pruning_check(&self, pool: &PgPool) -> Result<bool, StoreError> {
+        let (pruning_point, pruning_point_time) =
+            crate::kaspad::db::get_pruning_point(&self.storage)?;
+
+        if pruning_point_time <= self.window_start_time {
+            return Ok(true);
         }
 
+        let message = format!(
+            "Pruning point {} (at {}) is past window start. Window {} to {} is skipped as a whole, its data is permanently unavailable.",
+            pruning_point,
+            DateTime::from_timestamp_millis(pruning_point_time as i64).unwrap(),
+            DateTime::from_timestamp_millis(self.window_start_time as i64).unwrap(),
+            DateTime::from_timestamp_millis(self.window_end_time as i64).unwrap(),
+        );
+        error!("{}", message);
+
+        let sql = r#"
+            INSERT INTO data_gap
+            (start_time, end_time, reason, pruning_point)
+            VALUES
+            ($1, $2, $3, $4)
+            ON CONFLICT (start_time, end_time, reason) DO NOTHING
+        "#;
+
+        sqlx::query(sql)
+            .bind(DateTime::from_timestamp_millis(self.window_start_time as i64).unwrap())
+            .bind(DateTime::from_timestamp_millis(self.window_end_time as i64).unwrap())
+            .bind("pruned")
+            .bind(pruning_point.to_string())
+            .execute(pool)
+            .await
+            .unwrap();
+
+        crate::utils::alert::send(
+            &self.config,
+            format!("{} | kaspalytics-rs pruning alert", &self.config.env),
+            message,
+        )
+        .await;
+
+        Ok(false)
+    }
+}
+
+impl Analysis {
+    fn load_chain_blocks(&mut self) {
+        self.chain_blocks = crate::kaspad::db::get_chain_blocks_in_window(
+            &self.storage,
+            self.window_start_time,
+            self.window_end_time,
+        );
+
         info!(
             "{} chain blocks loaded from DbSelectedChainStore for target window",
             self.chain_blocks.len()
         );
     }
+}
 
-    // Reads utxo_diffs_store for given chain block
-    // Returns a single map of all UTXOs affected (created or removed) for chain block
-    fn get_utxos_for_chain_block(
+impl Analysis {
+    // Processes one chain block's mergeset into per second `stats`
+    // Returns IDs of transactions processed, which are added to `transaction_cache`
+    fn chain_block_analysis(
         &self,
         hash: Hash,
-    ) -> Result<HashMap<TransactionOutpoint, UtxoEntry>, StoreError> {
-        let utxo_diffs = self.storage.utxo_diffs_store.get(hash)?;
-        let mut utxos = HashMap::<TransactionOutpoint, UtxoEntry>::new();
+        transaction_cache: &mut HashSet<TransactionId>,
+        stats: &mut BTreeMap<u64, Stats>,
+    ) -> Result<Vec<TransactionId>, StoreError> {
+        let mut this_chain_blocks_merged_transactions = Vec::<TransactionId>::new();
 
-        utxo_diffs.removed().iter().for_each(|(outpoint, utxo)| {
-            utxos.insert(*outpoint, utxo.clone());
-        });
+        // Get acceptance data
+        let acceptances = self.storage.acceptance_data_store.get(hash)?;
 
-        utxo_diffs.added().iter().for_each(|(outpoint, utxo)| {
-            utxos.insert(*outpoint, utxo.clone());
-        });
+        // Load UTXOs from utxo diffs store
+        let utxos = crate::kaspad::db::get_utxos_for_chain_block(&self.storage, hash)?;
 
-        Ok(utxos)
-    }
-}
+        // Iterate blocks in current chain block's mergeset
+        for mergeset_data in acceptances.iter() {
+            let header = self
+                .storage
+                .headers_store
+                .get_header(mergeset_data.block_hash)?;
+            let transactions = self
+                .storage
+                .block_transactions_store
+                .get(mergeset_data.block_hash)?;
+            let is_chain_block = match self
+                .storage
+                .selected_chain_store
+                .read()
+                .get_by_hash(mergeset_data.block_hash)
+            {
+                Ok(_) => true,
+                Err(StoreError::KeyNotFound(_)) => false,
+                Err(_) => panic!(),
+            };
 
-impl Analysis {
-    fn tx_analysis(&mut self) -> Result<(), StoreError> {
-        let mut transaction_cache = std::collections::HashSet::<TransactionId>::new();
-        let mut tx_iter_order = std::collections::VecDeque::<Vec<TransactionId>>::new();
-
-        // Iterate chain blocks
-        for (i, (_, hash)) in self.chain_blocks.iter().skip(1).enumerate() {
-            let mut this_chain_blocks_merged_transactions = Vec::<TransactionId>::new();
-
-            // Get acceptance data
-            let acceptances = self.storage.acceptance_data_store.get(*hash)?;
-
-            // Load UTXOs from utxo diffs store
-            let utxos = self.get_utxos_for_chain_block(*hash)?;
-
-            // Iterate blocks in current chain block's mergeset
-            for mergeset_data in acceptances.iter() {
-                let header = self
-                    .storage
-                    .headers_store
-                    .get_header(mergeset_data.block_hash)?;
-                let transactions = self
-                    .storage
-                    .block_transactions_store
-                    .get(mergeset_data.block_hash)?;
-                let is_chain_block = match self
-                    .storage
-                    .selected_chain_store
-                    .read()
-                    .get_by_hash(mergeset_data.block_hash)
-                {
-                    Ok(_) => true,
-                    Err(StoreError::KeyNotFound(_)) => false,
-                    Err(_) => panic!(),
-                };
+            let block_time_s = header.timestamp / 1000;
 
-                let block_time_s = header.timestamp / 1000;
+            // Ensure stats entry for this second exists
+            stats
+                .entry(block_time_s)
+                .or_insert(Stats::new(block_time_s, Granularity::Second));
 
-                // Ensure stats entry for this second exists
-                self.stats
-                    .entry(block_time_s)
-                    .or_insert(Stats::new(block_time_s, Granularity::Second));
-
-                // Iterate transactions in the merged block
-                let mut accepted_transactions_in_this_block = 0;
-                for (tx_index, tx) in transactions.iter().enumerate() {
-                    // Skip transactions we already processed
-                    // This is a lazy (inefficient) approach to handle when a TX is in multiple blocks, and those blocks are not merged by same chain block
-                    if transaction_cache.contains(&tx.id()) {
-                        continue;
+            stats.entry(block_time_s).and_modify(|stats| {
+                stats.block_work += calc_work(header.bits).as_u128();
+            });
+
+            // Parse miner tag from coinbase transaction payload
+            match transactions
+                .first()
+                .map(|tx| CoinbasePayload::parse(&tx.payload, self.config.coinbase.strict_payload))
+            {
+                Some(Ok(payload)) => {
+                    if let Some(tag) = payload.miner_tag() {
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            *stats.block_count_per_miner_tag.entry(tag).or_insert(0) += 1
+                        });
                     }
 
-                    match (is_chain_block, tx_index) {
-                        (true, 0) => {
-                            // Coinbase transaction of chain block
-                            // Add to counters
-                            self.stats
-                                .entry(block_time_s)
-                                .and_modify(|stats| stats.coinbase_tx_count += 1);
+                    if let Some(version) = payload.node_version() {
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            *stats
+                                .block_count_per_node_version
+                                .entry(version.to_string())
+                                .or_insert(0) += 1
+                        });
+                    }
+                }
+                Some(Err(_)) | None => {
+                    stats
+                        .entry(block_time_s)
+                        .and_modify(|stats| stats[Counter::UnparsedCoinbasePayloadCount] += 1);
+                }
+            }
 
-                            self.stats.entry(block_time_s).and_modify(|stats| {
-                                stats.output_count_coinbase_tx += tx.outputs.len() as u64
-                            });
+            // Iterate transactions in the merged block
+            let mut accepted_transactions_in_this_block = 0;
+            let mut previous_payload: Option<&[u8]> = None;
+            for (tx_index, tx) in transactions.iter().enumerate() {
+                // Skip transactions we already processed
+                // This is a lazy (inefficient) approach to handle when a TX is in multiple blocks, and those blocks are not merged by same chain block
+                if transaction_cache.contains(&tx.id()) {
+                    continue;
+                }
 
-                            self.stats
-                                .entry(block_time_s)
-                                .and_modify(|stats| stats.spc_block_count += 1);
+                match (is_chain_block, tx_index) {
+                    (true, 0) => {
+                        // Coinbase transaction of chain block
+                        // Add to counters
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            stats[Counter::CoinbaseTxCount] += 1;
+                            stats.xor_tx_id(&tx.id().as_bytes());
+                        });
 
-                            accepted_transactions_in_this_block += 1;
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            stats[Counter::OutputCountCoinbaseTx] += tx.outputs.len() as u64
+                        });
 
-                            // Continue skips fee analysis since this is coinbase tx
-                            continue;
-                        }
-                        (false, 0) => {
-                            // Coinbase transaction of non-chain block
-                            // Skip processing as these are paid by chain block
-                            continue;
-                        }
-                        (_, _) => {
-                            // A regular transaction
-                            // Either part of chain block (at index 1+)
-                            // Or part of non-chain block (at index 1+)
-                            self.stats
-                                .entry(block_time_s)
-                                .and_modify(|stats| stats.regular_tx_count += 1);
-
-                            accepted_transactions_in_this_block += 1;
-                        }
-                    }
+                        stats
+                            .entry(block_time_s)
+                            .and_modify(|stats| stats[Counter::SpcBlockCount] += 1);
 
-                    // Count inputs of current transaction
-                    self.stats
-                        .entry(block_time_s)
-                        .and_modify(|stats| stats.input_count += tx.inputs.len() as u64);
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            *stats
+                                .tx_count_per_subnetwork
+                                .entry(tx.subnetwork_id.clone())
+                                .or_insert(0) += 1
+                        });
 
-                    // Count outputs of current transaction
-                    self.stats.entry(block_time_s).and_modify(|stats| {
-                        stats.output_count_regular_tx += tx.outputs.len() as u64
-                    });
+                        accepted_transactions_in_this_block += 1;
 
-                    let mut all_outpoints_resolved = true;
-                    let mut tx_fee = 0;
-                    for input in tx.inputs.iter() {
-                        let previous_outpoint = utxos.get(&input.previous_outpoint);
-                        match previous_outpoint {
-                            Some(previous_outpoint) => {
-                                tx_fee += previous_outpoint.amount;
-
-                                let address = extract_script_pub_key_address(
-                                    &previous_outpoint.script_public_key,
-                                    self.config.network_id.into(),
-                                )
-                                .unwrap();
-
-                                self.stats.entry(block_time_s).and_modify(|stats| {
-                                    stats.unique_senders.insert(address);
-                                });
-                            }
-                            None => {
-                                self.stats.entry(block_time_s).and_modify(|stats| {
-                                    stats.input_count_missing_previous_outpoints += 1
-                                });
-
-                                all_outpoints_resolved = false;
-                            }
-                        }
+                        // Continue skips fee analysis since this is coinbase tx
+                        continue;
                     }
-
-                    if !all_outpoints_resolved {
-                        self.stats
-                            .entry(block_time_s)
-                            .and_modify(|stats| stats.skipped_tx_count_cannot_resolve_inputs += 1);
+                    (false, 0) => {
+                        // Coinbase transaction of non-chain block
+                        // Skip processing as these are paid by chain block
                         continue;
                     }
+                    (_, _) => {
+                        // A regular transaction
+                        // Either part of chain block (at index 1+)
+                        // Or part of non-chain block (at index 1+)
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            stats[Counter::RegularTxCount] += 1;
+                            stats.xor_tx_id(&tx.id().as_bytes());
+                        });
 
-                    for output in tx.outputs.iter() {
-                        tx_fee -= output.value;
-                        let address = extract_script_pub_key_address(
-                            &output.script_public_key,
-                            self.config.network_id.into(),
-                        )
-                        .unwrap();
-                        self.stats.entry(block_time_s).and_modify(|stats| {
-                            stats.unique_recipients.insert(address);
+                        stats.entry(block_time_s).and_modify(|stats| {
+                            *stats
+                                .tx_count_per_subnetwork
+                                .entry(tx.subnetwork_id.clone())
+                                .or_insert(0) += 1
                         });
+
+                        accepted_transactions_in_this_block += 1;
                     }
+                }
 
-                    self.stats
+                // Count inputs of current transaction
+                stats
+                    .entry(block_time_s)
+                    .and_modify(|stats| stats[Counter::InputCount] += tx.inputs.len() as u64);
+
+                // Count outputs of current transaction
+                stats.entry(block_time_s).and_modify(|stats| {
+                    stats[Counter::OutputCountRegularTx] += tx.outputs.len() as u64
+                });
+
+                // Count load classification signals of current transaction
+                if !tx.payload.is_empty() {
+                    let repeated = previous_payload == Some(tx.payload.as_slice());
+                    stats.entry(block_time_s).and_modify(|stats| {
+                        stats[Counter::PayloadTxCount] += 1;
+                        stats[Counter::RepeatedPayloadTxCount] += repeated as u64;
+                    });
+                    previous_payload = Some(tx.payload.as_slice());
+                }
+
+                if load::is_inscription(tx) {
+                    stats
                         .entry(block_time_s)
-                        .and_modify(|stats| stats.fees.push(tx_fee));
+                        .and_modify(|stats| stats[Counter::InscriptionTxCount] += 1);
+                }
 
-                    transaction_cache.insert(tx.id());
-                    this_chain_blocks_merged_transactions.push(tx.id());
+                stats.entry(block_time_s).and_modify(|stats| {
+                    stats[Counter::SmallOutputCount] += tx
+                        .outputs
+                        .iter()
+                        .filter(|output| output.value < load::SMALL_OUTPUT_SOMPI)
+                        .count() as u64
+                });
+
+                let mut all_outpoints_resolved = true;
+                let mut tx_fee = 0;
+                let mut sender_addresses = Vec::<Address>::new();
+                for input in tx.inputs.iter() {
+                    let previous_outpoint = utxos.get(&input.previous_outpoint);
+                    match previous_outpoint {
+                        Some(previous_outpoint) => {
+                            tx_fee += previous_outpoint.amount;
+
+                            let address = extract_script_pub_key_address(
+                                &previous_outpoint.script_public_key,
+                                self.config.network_id.into(),
+                            )
+                            .unwrap();
+                            sender_addresses.push(address.clone());
+
+                            stats.entry(block_time_s).and_modify(|stats| {
+                                stats.unique_senders.insert(address);
+                            });
+                        }
+                        None => {
+                            stats.entry(block_time_s).and_modify(|stats| {
+                                stats[Counter::InputCountMissingPreviousOutpoints] += 1
+                            });
+
+                            all_outpoints_resolved = false;
+                        }
+                    }
                 }
 
-                self.stats.entry(block_time_s).and_modify(|stats| {
+                if !all_outpoints_resolved {
                     stats
-                        .transaction_count_per_block
-                        .push(accepted_transactions_in_this_block)
+                        .entry(block_time_s)
+                        .and_modify(|stats| stats[Counter::SkippedTxCountCannotResolveInputs] += 1);
+                    continue;
+                }
+
+                let mut transfer_volume = 0u64;
+                for output in tx.outputs.iter() {
+                    tx_fee -= output.value;
+                    let address = extract_script_pub_key_address(
+                        &output.script_public_key,
+                        self.config.network_id.into(),
+                    )
+                    .unwrap();
+
+                    // Outputs back to a sending address are change, not transferred value
+                    if !sender_addresses.contains(&address) {
+                        transfer_volume += output.value;
+                    }
+
+                    stats.entry(block_time_s).and_modify(|stats| {
+                        stats.unique_recipients.insert(address);
+                    });
+                }
+
+                stats
+                    .entry(block_time_s)
+                    .and_modify(|stats| stats.transfer_volume += transfer_volume as u128);
+
+                stats
+                    .entry(block_time_s)
+                    .and_modify(|stats| stats.fees.push(tx_fee));
+
+                // All inputs resolved above, so storage mass can be calculated
+                let entries = tx
+                    .inputs
+                    .iter()
+                    .map(|input| utxos.get(&input.previous_outpoint).unwrap().clone())
+                    .collect();
+                let compute_mass = self.mass_calculator.calc_tx_compute_mass(tx);
+                let storage_mass = self
+                    .mass_calculator
+                    .calc_tx_storage_mass(&PopulatedTransaction::new(tx, entries))
+                    .unwrap_or(0);
+                stats.entry(block_time_s).and_modify(|stats| {
+                    stats
+                        .tx_shape_per_version
+                        .entry(tx.version)
+                        .or_default()
+                        .push(compute_mass, storage_mass)
                 });
+
+                transaction_cache.insert(tx.id());
+                this_chain_blocks_merged_transactions.push(tx.id());
             }
 
-            tx_iter_order.push_back(this_chain_blocks_merged_transactions);
+            stats.entry(block_time_s).and_modify(|stats| {
+                stats
+                    .transaction_count_per_block
+                    .push(accepted_transactions_in_this_block)
+            });
+        }
+
+        Ok(this_chain_blocks_merged_transactions)
+    }
+
+    // Processes a contiguous range of chain blocks into its own per second stats
+    // `warm_up` are the chain blocks preceding the range. They are processed only to fill
+    // the dedup cache, so a transaction already accepted before the range is not counted again
+    fn chain_block_range_analysis(
+        &self,
+        warm_up: &[Hash],
+        range: &[Hash],
+    ) -> Result<BTreeMap<u64, Stats>, StoreError> {
+        let mut transaction_cache = HashSet::<TransactionId>::new();
+        let mut tx_iter_order = VecDeque::<Vec<TransactionId>>::new();
+
+        let mut warm_up_stats = BTreeMap::<u64, Stats>::new();
+        let mut stats = BTreeMap::<u64, Stats>::new();
+
+        for (i, hash) in warm_up.iter().chain(range.iter()).enumerate() {
+            let target = match i < warm_up.len() {
+                true => &mut warm_up_stats,
+                false => &mut stats,
+            };
 
-            if i >= 2700 {
+            let tx_ids = self.chain_block_analysis(*hash, &mut transaction_cache, target)?;
+            tx_iter_order.push_back(tx_ids);
+
+            // Only transactions of the last DEDUP_WINDOW chain blocks are kept in the cache
+            if tx_iter_order.len() > DEDUP_WINDOW {
                 if let Some(tx_ids) = tx_iter_order.pop_front() {
                     for tx_id in tx_ids {
                         transaction_cache.remove(&tx_id);
@@ -306,14 +503,360 @@ impl Analysis {
             }
         }
 
+        Ok(stats)
+    }
+
+    fn tx_analysis(&mut self) -> Result<(), StoreError> {
+        let hashes = self
+            .chain_blocks
+            .values()
+            .skip(1)
+            .copied()
+            .collect::<Vec<Hash>>();
+
+        if hashes.is_empty() {
+            return Ok(());
+        }
+
+        let threads = self.config.analysis.threads.min(hashes.len());
+        let range_size = hashes.len().div_ceil(threads);
+
+        info!(
+            "Processing {} chain blocks in ranges of {} across {} threads",
+            hashes.len(),
+            range_size,
+            threads
+        );
+
+        let analysis = &*self;
+        let results = std::thread::scope(|scope| {
+            let workers = (0..hashes.len())
+                .step_by(range_size)
+                .map(|start| {
+                    let end = (start + range_size).min(hashes.len());
+                    let warm_up = &hashes[start.saturating_sub(DEDUP_WINDOW)..start];
+                    let range = &hashes[start..end];
+                    scope.spawn(move || analysis.chain_block_range_analysis(warm_up, range))
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for result in results {
+            for (time, stats) in result? {
+                self.stats
+                    .entry(time)
+                    .and_modify(|existing| existing.merge(&stats))
+                    .or_insert(stats);
+            }
+        }
+
         Ok(())
     }
 }
 
 impl Analysis {
+    // Emails per minute anomalies of the window and saves them as annotations
+    async fn anomaly_analysis(&self, pool: &PgPool) {
+        let per_minute = Stats::rollup(&self.stats, Granularity::Minute);
+
+        let anomalies = [
+            (
+                AnomalyMetric::TransactionCount,
+                self.config.anomaly.tx_count_z_score,
+            ),
+            (AnomalyMetric::FeesTotal, self.config.anomaly.fees_z_score),
+        ]
+        .into_iter()
+        .flat_map(|(metric, z_threshold)| anomaly::detect(&per_minute, metric, z_threshold))
+        // Skip anomalies outside of time window
+        .filter(|anomaly| {
+            self.window_start_time <= anomaly.epoch_second * 1000
+                && anomaly.epoch_second * 1000 <= self.window_end_time
+        })
+        .collect::<Vec<Anomaly>>();
+
+        if anomalies.is_empty() {
+            return;
+        }
+
+        info!("{} per minute anomalies detected", anomalies.len());
+
+        for anomaly in anomalies.iter() {
+            Annotation {
+                start_time: DateTime::from_timestamp(anomaly.epoch_second as i64, 0).unwrap(),
+                end_time: DateTime::from_timestamp(
+                    (anomaly.epoch_second + Granularity::Minute.seconds() - 1) as i64,
+                    0,
+                )
+                .unwrap(),
+                label: format!("{} anomaly", anomaly.metric),
+                source: AnnotationSource::Detector,
+                note: Some(anomaly.to_string()),
+            }
+            .save(pool)
+            .await
+            .unwrap();
+        }
+
+        crate::utils::alert::send(
+            &self.config,
+            format!("{} | kaspalytics-rs anomaly alert", &self.config.env),
+            anomalies
+                .iter()
+                .map(|anomaly| anomaly.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+        .await;
+    }
+}
+
+impl Analysis {
+    // Saves hourly and daily hashrate estimated from block headers
+    // Hourly records are compared against the node's own estimate at the last chain block of the hour
+    async fn hashrate_analysis(&self, pool: &PgPool) {
+        let mut last_chain_block_per_hour = BTreeMap::<u64, Hash>::new();
+        let mut chain_blocks = Vec::<(u64, u128)>::new();
+        for hash in self.chain_blocks.values() {
+            let header = self.storage.headers_store.get_header(*hash).unwrap();
+            last_chain_block_per_hour
+                .insert(Granularity::Hour.truncate(header.timestamp / 1000), *hash);
+            chain_blocks.push((header.timestamp, header.blue_work.as_u128()));
+        }
+
+        let mut divergent = Vec::<HashrateEstimate>::new();
+        let mut dropped = Vec::<String>::new();
+
+        // The node's estimate is only compared against, so estimates are saved without it when no node is healthy
+        let rpc_client = match crate::kaspad::rpc::try_connect(&self.config).await {
+            Ok(rpc_client) => Some(rpc_client),
+            Err(e) => {
+                warn!("Saving hashrate without RPC estimates: {}", e);
+                None
+            }
+        };
+
+        for granularity in [Granularity::Hour, Granularity::Day] {
+            for (time, stats) in Stats::rollup(&self.stats, granularity) {
+                // Skip stat entries outside of time window
+                if time * 1000 < self.window_start_time || self.window_end_time < time * 1000 {
+                    continue;
+                }
+
+                let mut estimate = HashrateEstimate::from_stats(&stats);
+
+                let period = chain_blocks
+                    .iter()
+                    .filter(|(timestamp, _)| granularity.truncate(timestamp / 1000) == time)
+                    .copied()
+                    .collect::<Vec<(u64, u128)>>();
+                estimate.blue_work_hashrate = hashrate::blue_work_hashrate(&period);
+
+                if granularity == Granularity::Hour {
+                    if let (Some(rpc_client), Some(hash)) =
+                        (&rpc_client, last_chain_block_per_hour.get(&time))
+                    {
+                        estimate.rpc_hashrate = rpc_client
+                            .estimate_network_hashes_per_second(
+                                RPC_ESTIMATE_WINDOW_SIZE,
+                                Some(*hash),
+                            )
+                            .await
+                            .map(|hashrate| hashrate as f64)
+                            .ok();
+                    }
+                }
+
+                info!("{:?}", estimate);
+                estimate.save(pool).await;
+
+                if granularity == Granularity::Day {
+                    if let Some(previous) = estimate.load_previous_hashrate(pool).await {
+                        if let Some(drop) = estimate
+                            .drop_from(previous)
+                            .filter(|drop| *drop > self.config.alert.hashrate_drop)
+                        {
+                            dropped.push(format!(
+                                "{} hashrate {:.0} is {:.1}% below previous day's {:.0}",
+                                DateTime::from_timestamp(time as i64, 0)
+                                    .unwrap()
+                                    .date_naive(),
+                                estimate.estimated_hashrate,
+                                drop * 100.0,
+                                previous
+                            ));
+                        }
+                    }
+                }
+
+                if estimate
+                    .blue_work_divergence()
+                    .is_some_and(|d| d.abs() > self.config.anomaly.hashrate_divergence)
+                {
+                    divergent.push(estimate);
+                }
+            }
+        }
+
+        if let Some(rpc_client) = rpc_client {
+            let _ = rpc_client.disconnect().await;
+        }
+
+        if !divergent.is_empty() {
+            crate::utils::alert::send(
+                &self.config,
+                format!(
+                    "{} | kaspalytics-rs hashrate divergence alert",
+                    self.config.env
+                ),
+                divergent
+                    .iter()
+                    .map(|estimate| format!("{:?}", estimate))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            )
+            .await;
+        }
+
+        if !dropped.is_empty() {
+            crate::utils::alert::send(
+                &self.config,
+                format!("{} | kaspalytics-rs hashrate drop alert", self.config.env),
+                dropped.join("\n"),
+            )
+            .await;
+        }
+    }
+}
+
+impl Analysis {
+    // Records miner tags not yet known to the miner_tag registry
+    // and hourly block attribution per pool
+    async fn miner_tag_analysis(&self, pool: &PgPool) {
+        let registry = MinerTagRegistry::load(pool).await.unwrap();
+
+        for (time, stats) in Stats::rollup(&self.stats, Granularity::Hour) {
+            // Skip stat entries outside of time window
+            if time * 1000 < self.window_start_time || self.window_end_time < time * 1000 {
+                continue;
+            }
+
+            registry.save_pool_blocks(pool, &stats).await.unwrap();
+        }
+
+        for (time, stats) in Stats::rollup(&self.stats, Granularity::Day) {
+            // Skip stat entries outside of time window
+            if time * 1000 < self.window_start_time || self.window_end_time < time * 1000 {
+                continue;
+            }
+
+            let date = DateTime::from_timestamp(time as i64, 0)
+                .unwrap()
+                .date_naive();
+            let unmatched = registry
+                .save_unmatched(pool, date, &stats.block_count_per_miner_tag)
+                .await
+                .unwrap();
+
+            info!(
+                "{} of {} miner tags on {} not matched by miner_tag registry",
+                unmatched,
+                stats.block_count_per_miner_tag.len(),
+                date
+            );
+        }
+    }
+}
+
+impl Analysis {
+    // Saves hourly and daily block share per mining node version
+    async fn node_version_analysis(&self, pool: &PgPool) {
+        for granularity in [Granularity::Hour, Granularity::Day] {
+            for (time, stats) in Stats::rollup(&self.stats, granularity) {
+                // Skip stat entries outside of time window
+                if time * 1000 < self.window_start_time || self.window_end_time < time * 1000 {
+                    continue;
+                }
+
+                if let Err(e) = versions::save_node_version_shares(pool, &stats).await {
+                    error!("Failed to save node version shares of {}: {}", time, e);
+                }
+            }
+        }
+    }
+}
+
+impl Analysis {
+    // Saves hourly classification of inscription driven vs organic load
+    async fn load_analysis(&self, pool: &PgPool) {
+        for (time, stats) in Stats::rollup(&self.stats, Granularity::Hour) {
+            // Skip stat entries outside of time window
+            if time * 1000 < self.window_start_time || self.window_end_time < time * 1000 {
+                continue;
+            }
+
+            let classification = LoadClassification::from_stats(&stats);
+            info!("{:?}", classification);
+            classification.save(pool).await;
+
+            if classification.class == LoadClass::Inscription {
+                Annotation {
+                    start_time: DateTime::from_timestamp(time as i64, 0).unwrap(),
+                    end_time: DateTime::from_timestamp(
+                        (time + Granularity::Hour.seconds() - 1) as i64,
+                        0,
+                    )
+                    .unwrap(),
+                    label: String::from("inscription storm"),
+                    source: AnnotationSource::Detector,
+                    note: Some(format!(
+                        "{} of {} regular transactions were inscriptions",
+                        classification.inscription_tx_count, classification.regular_tx_count
+                    )),
+                }
+                .save(pool)
+                .await
+                .unwrap();
+            }
+        }
+    }
+}
+
+impl Analysis {
+    // Recomputes per day stats of [start_time, end_time] without saving anything
+    pub fn daily_stats(
+        config: Config,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<BTreeMap<u64, Stats>, StoreError> {
+        let storage = crate::kaspad::db::init_consensus_storage(
+            config.network_id,
+            &config.kaspad_dirs.active_consensus_db_dir,
+        );
+
+        let mut process = Analysis::new_from_time_window(config, storage, start_time, end_time);
+        process.load_chain_blocks();
+        process.tx_analysis()?;
+
+        Ok(Stats::rollup(&process.stats, Granularity::Day)
+            .into_iter()
+            // Skip stat entries outside of time window
+            .filter(|(time, _)| start_time <= time * 1000 && time * 1000 <= end_time)
+            .collect())
+    }
+
     pub async fn run(&mut self, pool: &PgPool) -> Result<(), StoreError> {
         // TODO custom error that wraps StoreError, other error types...
 
+        if !self.pruning_check(pool).await? {
+            return Ok(());
+        }
+
         self.load_chain_blocks();
 
         self.tx_analysis()?;
@@ -327,78 +870,205 @@ impl Analysis {
             }
 
             info!("{:?}", stats);
-            stats.save(pool).await;
+            retry(
+                "Stats::save",
+                &save_backoff(),
+                || stats.save(pool),
+                classify_error,
+            )
+            .await
+            .unwrap();
+            DailyChecksum::from_stats(&stats).save(pool).await;
 
-            crate::utils::email::send_email(
+            if self.config.analysis.enabled(AnalysisModule::Velocity) {
+                let date = DateTime::from_timestamp(time as i64, 0)
+                    .unwrap()
+                    .date_naive();
+                velocity::update(pool, date).await.unwrap();
+            }
+
+            if let Err(e) = crate::utils::email::send_email(
                 &self.config,
                 format!("{} | kaspalytics-rs stats results", &self.config.env),
                 format!("{:?}", stats),
-            );
+            )
+            .await
+            {
+                error!("Stats results email not sent: {}", e);
+            }
+        }
+
+        info!(
+            "Lifetime network totals: {}",
+            NetworkTotals::load(pool).await.unwrap()
+        );
+
+        if self.config.analysis.enabled(AnalysisModule::SecondsMetrics) {
+            let seconds = self
+                .stats
+                .iter()
+                // Skip stat entries outside of time window
+                .filter(|(time, _)| {
+                    self.window_start_time <= *time * 1000 && *time * 1000 <= self.window_end_time
+                })
+                .map(|(_, stats)| stats)
+                .collect::<Vec<&Stats>>();
+            retry(
+                "Stats::save_seconds_metrics",
+                &save_backoff(),
+                || Stats::save_seconds_metrics(pool, &seconds),
+                classify_error,
+            )
+            .await
+            .unwrap();
+        }
+
+        if self.config.analysis.enabled(AnalysisModule::Anomaly) {
+            self.anomaly_analysis(pool).await;
+        }
+
+        if self.config.analysis.enabled(AnalysisModule::Hashrate) {
+            self.hashrate_analysis(pool).await;
+        }
+
+        if self.config.analysis.enabled(AnalysisModule::MinerTag) {
+            self.miner_tag_analysis(pool).await;
+        }
+
+        if self.config.analysis.enabled(AnalysisModule::Load) {
+            self.load_analysis(pool).await;
+        }
+
+        if self.config.analysis.enabled(AnalysisModule::NodeVersion) {
+            self.node_version_analysis(pool).await;
         }
 
         Ok(())
     }
 
-    pub async fn main(config: Config, pool: &PgPool) {
+    // Analyses yesterday, or when start_time is given, backfills [start_time, end_time] one day at a time
+    // end_time defaults to end of yesterday
+    pub async fn main(
+        config: Config,
+        pool: &PgPool,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) {
+        info!(
+            "Enabled analysis modules: {:?}",
+            config.analysis.enabled_modules()
+        );
+
+        // The UTXO set only reflects the node's current state, so it is not backfilled
+        let snapshot_utxos = start_time.is_none() && config.analysis.enabled(AnalysisModule::Utxo);
+
+        let windows = match start_time {
+            None => vec![yesterday_window()],
+            Some(start_time) => {
+                // Today is not over yet, so backfill stops at the end of yesterday
+                let end_of_yesterday = yesterday_window().1;
+                let end_time = end_time.unwrap_or(end_of_yesterday).min(end_of_yesterday);
+                if end_time < start_time {
+                    error!("Analysis window starts after yesterday, nothing to analyse");
+                    return;
+                }
+                daily_windows(start_time, end_time)
+            }
+        };
+
+        // Snapshot is dated as the analysed day, like every other daily table of the run,
+        // and taken first so velocity of that day finds its circulating supply
+        if snapshot_utxos {
+            let date = DateTime::from_timestamp_millis(windows[0].0 as i64)
+                .unwrap()
+                .date_naive();
+            UtxoAnalysis::main(config.clone(), pool, date).await;
+        }
+
+        for (i, (start_time, end_time)) in windows.iter().enumerate() {
+            info!(
+                "Analysis window {}/{}: {} to {}",
+                i + 1,
+                windows.len(),
+                DateTime::from_timestamp_millis(*start_time as i64).unwrap(),
+                DateTime::from_timestamp_millis(*end_time as i64).unwrap(),
+            );
+
+            Self::main_for_window(&config, pool, *start_time, *end_time).await;
+        }
+    }
+
+    async fn main_for_window(config: &Config, pool: &PgPool, start_time: u64, end_time: u64) {
         // Sporadically (once a week-ish) a RocksDB error will be raised:
         // "Error rocksdb error IO error: No such file or directory: While open a file for random read: rusty-kaspa/kaspa-mainnet/datadir/consensus/consensus-002/1504776.sst: No such file or directory while getting block cb0c56da0c4c7948c5bf29c0f8eddbde11fc02df7641a2f27053c702bb96aef5 from database"
         // I have a hunch that is because this program is running while node pruning is in progress
         // And that during/after pruning, RocksDB is performing compaction
         // The read_only connection is supposed to create a snapshot (I think?) and prevent this (again, or so I think)...
-        // The below loop is an attempt to catch this error and retry every X minutes for up to X retry attempts
-        // Let's see how this goes...
-        let mut retries = 0;
-        let max_retries = 24;
-        let retry_delay = std::time::Duration::from_secs(5 * 60);
-
-        loop {
-            let storage = crate::kaspad::db::init_consensus_storage(
-                config.network_id,
-                &config.kaspad_dirs.active_consensus_db_dir,
-            );
+        // So DbError is retried, reopening RocksDB on each attempt, for up to ~2 hours
+        let backoff = Backoff::new(Duration::from_secs(60), Duration::from_secs(15 * 60))
+            .with_max_elapsed(Duration::from_secs(2 * 60 * 60));
 
-            let mut process = Analysis::new_for_yesterday(config.clone(), storage.clone());
-
-            match process.run(pool).await {
-                Ok(_) => break,
-                Err(StoreError::DbError(_)) if retries < max_retries => {
-                    // Close database connection before sleeping
-                    // Inside retries window. Sleep and try again
-                    drop(process);
-                    drop(storage);
-
-                    retries += 1;
-                    error!(
-                        "Database error on tx_analysis attempt {}/{}. Retrying in 5 minutes...",
-                        retries, max_retries
-                    );
-                    sleep(retry_delay).await;
-                }
-                Err(StoreError::DbError(_)) => {
-                    // After max retries, send alert email and exit
-                    error!(
-                        "Analysis::tx_analysis failed after {} attempts. Exiting...",
-                        retries
-                    );
-                    crate::utils::email::send_email(
-                        &config,
-                        format!("{} | kaspalytics-rs alert", config.env),
-                        "Analysis::tx_analysis reached max retries due to database error."
-                            .to_string(),
-                    );
-                    break;
-                }
-                Err(e) => {
-                    // Handle other errors and exit
-                    error!("Analysis::tx_analysis failed with error: {:?}", e);
-                    crate::utils::email::send_email(
-                        &config,
-                        format!("{} | kaspalytics-rs alert", config.env),
-                        format!("Analysis::tx_analysis failed with error: {:?}", e),
-                    );
-                    break;
-                }
-            }
+        let result = retry(
+            "Analysis::run",
+            &backoff,
+            || async move {
+                let storage = crate::kaspad::db::init_consensus_storage(
+                    config.network_id,
+                    &config.kaspad_dirs.active_consensus_db_dir,
+                );
+
+                Analysis::new_from_time_window(config.clone(), storage, start_time, end_time)
+                    .run(pool)
+                    .await
+            },
+            |e| match e {
+                StoreError::DbError(_) => Retry::Transient,
+                _ => Retry::Permanent,
+            },
+        )
+        .await;
+
+        if let Err(e) = result {
+            error!("Analysis::run failed with error: {:?}", e);
+            crate::utils::alert::send(
+                config,
+                format!("{} | kaspalytics-rs alert", config.env),
+                format!("Analysis::run failed with error: {:?}", e),
+            )
+            .await;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::daily_windows;
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    // 2024-07-01 00:00:00 UTC
+    const JULY_1: u64 = 1_719_792_000_000;
+
+    #[test]
+    fn daily_windows_snap_to_whole_days() {
+        // 2024-07-01 06:00 to 2024-07-03 00:00
+        let windows = daily_windows(JULY_1 + 6 * 60 * 60 * 1000, JULY_1 + 2 * DAY_MS);
+
+        assert_eq!(
+            windows,
+            vec![
+                (JULY_1, JULY_1 + DAY_MS - 1),
+                (JULY_1 + DAY_MS, JULY_1 + 2 * DAY_MS - 1),
+                (JULY_1 + 2 * DAY_MS, JULY_1 + 3 * DAY_MS - 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn daily_windows_single_day() {
+        assert_eq!(
+            daily_windows(JULY_1, JULY_1 + DAY_MS - 1),
+            vec![(JULY_1, JULY_1 + DAY_MS - 1)]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::cli::AnnotationCommands;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::fmt;
+use strum_macros::{Display, EnumString};
+
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum AnnotationSource {
+    // Added by an operator through the CLI
+    Operator,
+
+    // Added automatically by an analysis detector
+    Detector,
+}
+
+// Label attached to a time range, i.e. "hardfork activation" or "inscription storm"
+#[derive(Debug)]
+pub struct Annotation {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub label: String,
+    pub source: AnnotationSource,
+    pub note: Option<String>,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} to {} [{}] {}",
+            self.start_time, self.end_time, self.source, self.label
+        )?;
+
+        match &self.note {
+            Some(note) => write!(f, ": {}", note),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Annotation {
+    // Saves annotation, an identical annotation already saved is left as is
+    pub async fn save(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO annotations
+            (start_time, end_time, label, source, note)
+            VALUES
+            ($1, $2, $3, $4, $5)
+            ON CONFLICT (start_time, end_time, label, source) DO NOTHING
+        "#;
+
+        sqlx::query(sql)
+            .bind(self.start_time)
+            .bind(self.end_time)
+            .bind(&self.label)
+            .bind(self.source.to_string())
+            .bind(&self.note)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Annotations whose time range overlaps [start_time, end_time]
+    pub async fn overlapping(
+        pool: &PgPool,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let sql = r#"
+            SELECT start_time, end_time, label, source, note
+            FROM annotations
+            WHERE start_time <= $2 AND end_time >= $1
+            ORDER BY start_time
+        "#;
+
+        let rows: Vec<(DateTime<Utc>, DateTime<Utc>, String, String, Option<String>)> =
+            sqlx::query_as(sql)
+                .bind(start_time)
+                .bind(end_time)
+                .fetch_all(pool)
+                .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(start_time, end_time, label, source, note)| Self {
+                start_time,
+                end_time,
+                label,
+                source: source.parse().unwrap(),
+                note,
+            })
+            .collect())
+    }
+}
+
+pub async fn run(pool: &PgPool, command: AnnotationCommands) {
+    match command {
+        AnnotationCommands::Add {
+            start_time,
+            end_time,
+            label,
+            note,
+        } => {
+            let annotation = Annotation {
+                start_time: DateTime::from_timestamp_millis(start_time as i64).unwrap(),
+                end_time: DateTime::from_timestamp_millis(end_time as i64).unwrap(),
+                label,
+                source: AnnotationSource::Operator,
+                note,
+            };
+            annotation.save(pool).await.unwrap();
+
+            println!("{}", annotation);
+        }
+        AnnotationCommands::List {
+            start_time,
+            end_time,
+        } => {
+            let annotations = Annotation::overlapping(
+                pool,
+                DateTime::from_timestamp_millis(start_time as i64).unwrap(),
+                DateTime::from_timestamp_millis(end_time as i64).unwrap(),
+            )
+            .await
+            .unwrap();
+
+            for annotation in annotations {
+                println!("{}", annotation);
+            }
+        }
+    }
+}
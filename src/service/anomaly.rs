@@ -0,0 +1,150 @@
+use crate::service::stats::Stats;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use strum_macros::Display;
+
+// Number of trailing entries used as the baseline for each observation
+const BASELINE_WINDOW: usize = 60;
+
+#[derive(Clone, Copy, Debug, Display)]
+pub enum AnomalyMetric {
+    TransactionCount,
+    FeesTotal,
+}
+
+impl AnomalyMetric {
+    fn value(&self, stats: &Stats) -> f64 {
+        match self {
+            AnomalyMetric::TransactionCount => stats.tx_count() as f64,
+            AnomalyMetric::FeesTotal => stats.fees.iter().sum::<u64>() as f64,
+        }
+    }
+}
+
+pub struct Anomaly {
+    pub metric: AnomalyMetric,
+    pub epoch_second: u64,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub z_score: f64,
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} spike at epoch second {}: value {:.2}, baseline mean {:.2}, z-score {:.2}",
+            self.metric, self.epoch_second, self.value, self.baseline_mean, self.z_score
+        )
+    }
+}
+
+// Flags entries whose metric value exceeds the mean of the trailing
+// BASELINE_WINDOW entries by at least `z_threshold` standard deviations.
+// `stats` is expected to be a rollup at a single granularity (i.e. per minute).
+pub fn detect(
+    stats: &BTreeMap<u64, Stats>,
+    metric: AnomalyMetric,
+    z_threshold: f64,
+) -> Vec<Anomaly> {
+    let mut baseline = VecDeque::<f64>::with_capacity(BASELINE_WINDOW);
+    let mut anomalies = Vec::<Anomaly>::new();
+
+    for (epoch_second, stats) in stats {
+        let value = metric.value(stats);
+
+        if baseline.len() == BASELINE_WINDOW {
+            let mean = baseline.iter().sum::<f64>() / BASELINE_WINDOW as f64;
+            let variance =
+                baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / BASELINE_WINDOW as f64;
+            let std_dev = variance.sqrt();
+
+            if std_dev > 0.0 {
+                let z_score = (value - mean) / std_dev;
+                if z_score >= z_threshold {
+                    anomalies.push(Anomaly {
+                        metric,
+                        epoch_second: *epoch_second,
+                        value,
+                        baseline_mean: mean,
+                        z_score,
+                    });
+                }
+            }
+
+            baseline.pop_front();
+        }
+
+        baseline.push_back(value);
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, AnomalyMetric, BASELINE_WINDOW};
+    use crate::service::stats::{Counter, Stats};
+    use crate::utils::granularity::Granularity;
+    use std::collections::BTreeMap;
+
+    // 2024-07-01T00:00:00Z
+    const JULY_1: u64 = 1_719_792_000;
+
+    // Per minute stats with the given transaction counts
+    fn minutes(tx_counts: &[u64]) -> BTreeMap<u64, Stats> {
+        tx_counts
+            .iter()
+            .enumerate()
+            .map(|(i, tx_count)| {
+                let epoch_second = JULY_1 + i as u64 * 60;
+                let mut stats = Stats::new(epoch_second, Granularity::Minute);
+                stats[Counter::RegularTxCount] = *tx_count;
+                (epoch_second, stats)
+            })
+            .collect()
+    }
+
+    // Baseline alternating between 99 and 101, so mean 100 and standard deviation 1
+    fn baseline(len: usize) -> Vec<u64> {
+        (0..len).map(|i| 99 + (i as u64 % 2) * 2).collect()
+    }
+
+    #[test]
+    fn flat_series_has_no_anomaly() {
+        let stats = minutes(&[100; 2 * BASELINE_WINDOW]);
+        assert!(detect(&stats, AnomalyMetric::TransactionCount, 4.0).is_empty());
+    }
+
+    #[test]
+    fn spike_is_flagged() {
+        let mut tx_counts = baseline(BASELINE_WINDOW);
+        tx_counts.push(110);
+        tx_counts.push(103);
+
+        let anomalies = detect(&minutes(&tx_counts), AnomalyMetric::TransactionCount, 4.0);
+
+        assert_eq!(anomalies.len(), 1);
+        let anomaly = &anomalies[0];
+        assert_eq!(anomaly.epoch_second, JULY_1 + BASELINE_WINDOW as u64 * 60);
+        assert_eq!(anomaly.value, 110.0);
+        assert_eq!(anomaly.baseline_mean, 100.0);
+        assert!((anomaly.z_score - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn drop_is_not_flagged() {
+        let mut tx_counts = baseline(BASELINE_WINDOW);
+        tx_counts.push(0);
+
+        assert!(detect(&minutes(&tx_counts), AnomalyMetric::TransactionCount, 4.0).is_empty());
+    }
+
+    #[test]
+    fn short_window_has_no_baseline() {
+        let mut tx_counts = baseline(BASELINE_WINDOW - 1);
+        tx_counts.push(10_000);
+
+        assert!(detect(&minutes(&tx_counts), AnomalyMetric::TransactionCount, 4.0).is_empty());
+    }
+}
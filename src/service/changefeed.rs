@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use std::fmt;
+use strum_macros::Display;
+
+#[derive(Clone, Copy, Debug, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Entity {
+    // Daily summary tables of one date, saved by Stats::save
+    DailyStats,
+
+    // Rich list snapshot of one date
+    RichList,
+}
+
+#[derive(Clone, Copy, Debug, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Op {
+    Insert,
+    Upsert,
+}
+
+// Records a write to `entity` in the changefeed
+// Must run in the same transaction as the write, so the entry is committed if and only if the write is
+// Entries of one transaction share the batch id (PG transaction id)
+pub async fn append(
+    conn: &mut PgConnection,
+    entity: Entity,
+    entity_id: String,
+    op: Op,
+) -> Result<(), sqlx::Error> {
+    let sql = r#"
+        INSERT INTO changefeed
+        (entity_type, entity_id, op, batch_id)
+        VALUES
+        ($1, $2, $3, txid_current())
+    "#;
+
+    sqlx::query(sql)
+        .bind(entity.to_string())
+        .bind(entity_id)
+        .bind(op.to_string())
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub struct ChangefeedEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub op: String,
+    pub batch_id: i64,
+    pub committed_at: DateTime<Utc>,
+}
+
+impl fmt::Display for ChangefeedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.id, self.batch_id, self.committed_at, self.entity_type, self.entity_id, self.op
+        )
+    }
+}
+
+// Entries after cursor `after_id`, oldest first
+// Consumers pass the id of the last entry processed as the next cursor
+// Writes are sequential (one analysis process at a time), so ids are committed in order
+pub async fn read(
+    pool: &PgPool,
+    after_id: i64,
+    limit: i64,
+) -> Result<Vec<ChangefeedEntry>, sqlx::Error> {
+    let sql = r#"
+        SELECT id, entity_type, entity_id, op, batch_id, committed_at
+        FROM changefeed
+        WHERE id > $1
+        ORDER BY id
+        LIMIT $2
+    "#;
+
+    let rows: Vec<(i64, String, String, String, i64, DateTime<Utc>)> = sqlx::query_as(sql)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(id, entity_type, entity_id, op, batch_id, committed_at)| ChangefeedEntry {
+                id,
+                entity_type,
+                entity_id,
+                op,
+                batch_id,
+                committed_at,
+            },
+        )
+        .collect())
+}
+
+pub async fn run(pool: &PgPool, after_id: i64, limit: i64) {
+    for entry in read(pool, after_id, limit).await.unwrap() {
+        println!("{}", entry);
+    }
+}
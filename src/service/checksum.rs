@@ -0,0 +1,168 @@
+use crate::database::Database;
+use crate::service::analysis::{daily_windows, Analysis};
+use crate::service::stats::{Counter, Stats};
+use crate::utils::config::Config;
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{error, info, warn};
+use sqlx::PgPool;
+use std::fmt;
+
+// Integrity checksum of one day of analysed data
+// Two instances analysing the same node data produce the same checksum, regardless of processing order
+#[derive(Debug, PartialEq)]
+pub struct DailyChecksum {
+    pub date: NaiveDate,
+    pub spc_block_count: u64,
+    pub tx_count: u64,
+    pub fees_total: u64,
+
+    // Hex encoded XOR of IDs of counted transactions
+    pub tx_id_xor: String,
+}
+
+impl fmt::Display for DailyChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "spc_blocks={} txs={} fees={} tx_id_xor={}",
+            self.spc_block_count, self.tx_count, self.fees_total, self.tx_id_xor
+        )
+    }
+}
+
+impl DailyChecksum {
+    pub fn from_stats(stats: &Stats) -> Self {
+        Self {
+            date: DateTime::from_timestamp(stats.epoch_second as i64, 0)
+                .unwrap()
+                .date_naive(),
+            spc_block_count: stats[Counter::SpcBlockCount],
+            tx_count: stats.tx_count(),
+            fees_total: stats.fees.iter().sum(),
+            tx_id_xor: stats
+                .tx_id_xor
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        }
+    }
+
+    // Saves checksum, replacing any checksum previously saved for the date
+    pub async fn save(&self, pool: &PgPool) {
+        let sql = r#"
+            INSERT INTO daily_checksum
+            (date, spc_block_count, tx_count, fees_total, tx_id_xor)
+            VALUES
+            ($1, $2, $3, $4, $5)
+            ON CONFLICT (date) DO UPDATE SET
+                spc_block_count = EXCLUDED.spc_block_count,
+                tx_count = EXCLUDED.tx_count,
+                fees_total = EXCLUDED.fees_total,
+                tx_id_xor = EXCLUDED.tx_id_xor,
+                computed_at = CURRENT_TIMESTAMP
+        "#;
+
+        sqlx::query(sql)
+            .bind(self.date)
+            .bind(self.spc_block_count as i64)
+            .bind(self.tx_count as i64)
+            .bind(self.fees_total as i64)
+            .bind(&self.tx_id_xor)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    pub async fn load(pool: &PgPool, date: NaiveDate) -> Result<Option<Self>, sqlx::Error> {
+        let sql = r#"
+            SELECT spc_block_count, tx_count, fees_total, tx_id_xor
+            FROM daily_checksum
+            WHERE date = $1
+        "#;
+
+        let row: Option<(i64, i64, i64, String)> =
+            sqlx::query_as(sql).bind(date).fetch_optional(pool).await?;
+
+        Ok(
+            row.map(|(spc_block_count, tx_count, fees_total, tx_id_xor)| Self {
+                date,
+                spc_block_count: spc_block_count as u64,
+                tx_count: tx_count as u64,
+                fees_total: fees_total as u64,
+                tx_id_xor,
+            }),
+        )
+    }
+}
+
+// Compares a stored checksum against the recomputed one, returns false on mismatch
+fn compare(source: &str, recomputed: &DailyChecksum, stored: Option<DailyChecksum>) -> bool {
+    match stored {
+        None => {
+            info!("{} {}: no checksum", recomputed.date, source);
+            true
+        }
+        Some(stored) if stored == *recomputed => {
+            info!("{} {}: OK", recomputed.date, source);
+            true
+        }
+        Some(stored) => {
+            error!(
+                "{} {}: MISMATCH, recomputed {} but found {}",
+                recomputed.date, source, recomputed, stored
+            );
+            false
+        }
+    }
+}
+
+// Recomputes daily checksums of every whole UTC day touched by [start_time, end_time] from node data
+// and compares them against checksums stored in PG, and in the PG database of a peer instance when given
+// Checksums are stored per whole day, so partial days are never compared. Today is skipped as it is not over yet
+// Exits with status 1 when any checksum does not match
+pub async fn verify(
+    config: &Config,
+    pool: &PgPool,
+    start_time: u64,
+    end_time: u64,
+    peer_db_uri: Option<String>,
+) {
+    let peer_pool = match peer_db_uri {
+        Some(uri) => Some(Database::new(uri).open_connection_pool(1).await.unwrap()),
+        None => None,
+    };
+
+    let now = Utc::now().timestamp_millis() as u64;
+
+    let mut all_match = true;
+    for (start_time, end_time) in daily_windows(start_time, end_time) {
+        if now <= end_time {
+            warn!(
+                "{} is not over yet, skipped",
+                DateTime::from_timestamp_millis(start_time as i64)
+                    .unwrap()
+                    .date_naive()
+            );
+            continue;
+        }
+
+        for (_, stats) in Analysis::daily_stats(config.clone(), start_time, end_time).unwrap() {
+            let recomputed = DailyChecksum::from_stats(&stats);
+            info!("{} recomputed: {}", recomputed.date, recomputed);
+
+            let stored = DailyChecksum::load(pool, recomputed.date).await.unwrap();
+            all_match &= compare("stored", &recomputed, stored);
+
+            if let Some(peer_pool) = &peer_pool {
+                let peer = DailyChecksum::load(peer_pool, recomputed.date)
+                    .await
+                    .unwrap();
+                all_match &= compare("peer", &recomputed, peer);
+            }
+        }
+    }
+
+    if !all_match {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::utils::config::Config;
+use kaspa_consensus_core::errors::consensus::ConsensusError;
+use kaspa_consensus_core::Hash;
+use kaspa_rpc_core::api::rpc::RpcApi;
+use kaspa_rpc_core::RpcError;
+use kaspa_wrpc_client::KaspaRpcClient;
+use log::{error, info, warn};
+use sqlx::PgPool;
+use std::fmt;
+use strum_macros::Display;
+
+#[derive(Clone, Copy, Debug, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum SplitKind {
+    // Node's virtual DAA score is more than CONSENSUS_MAX_DAA_LAG behind the reference node
+    Lagging,
+
+    // Node is close to the reference node's DAA score but reports a different pruning point
+    PruningPointMismatch,
+
+    // Node's sink is not known to the reference node
+    UnknownSink,
+}
+
+// Divergence of a node from the reference node, the node with the highest virtual DAA score
+#[derive(Debug)]
+pub struct SplitEvent {
+    pub kind: SplitKind,
+    pub reference_url: String,
+    pub node_url: String,
+    pub reference_daa_score: u64,
+    pub node_daa_score: u64,
+    pub detail: String,
+}
+
+impl fmt::Display for SplitEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} (daa score {}) vs {} (daa score {}): {}",
+            self.kind,
+            self.node_url,
+            self.node_daa_score,
+            self.reference_url,
+            self.reference_daa_score,
+            self.detail
+        )
+    }
+}
+
+impl SplitEvent {
+    async fn save(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO consensus_split_event
+            (kind, reference_url, node_url, reference_daa_score, node_daa_score, detail)
+            VALUES
+            ($1, $2, $3, $4, $5, $6)
+        "#;
+
+        sqlx::query(sql)
+            .bind(self.kind.to_string())
+            .bind(&self.reference_url)
+            .bind(&self.node_url)
+            .bind(self.reference_daa_score as i64)
+            .bind(self.node_daa_score as i64)
+            .bind(&self.detail)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Whether get_block failed because the node doesn't have the block, as opposed to i.e. a connection error
+// Over wRPC the node's ConsensusError arrives as its message, so that is matched too
+fn is_block_not_found(e: &RpcError) -> bool {
+    match e {
+        RpcError::ConsensusError(ConsensusError::BlockNotFound(_)) => true,
+        RpcError::General(message) | RpcError::RpcSubsystem(message) => {
+            message.contains("cannot find full block")
+        }
+        _ => false,
+    }
+}
+
+// View of the DAG reported by one node
+struct NodeView {
+    url: String,
+    rpc_client: KaspaRpcClient,
+    virtual_daa_score: u64,
+    pruning_point: Hash,
+    sink: Hash,
+}
+
+// Compares chain state of RPC_URL and PROBE_RPC_URLS nodes, records and emails divergences
+pub async fn monitor(config: &Config, pool: &PgPool) {
+    let mut urls = vec![config.rpc.url.clone()];
+    for url in config.rpc.probe_urls.iter() {
+        if !urls.contains(url) {
+            urls.push(url.clone());
+        }
+    }
+
+    let mut views = Vec::<NodeView>::new();
+    for url in urls {
+        let rpc_client = match crate::kaspad::rpc::connect_to(config, &url).await {
+            Ok(rpc_client) => rpc_client,
+            Err(e) => {
+                warn!("Skipping RPC node {}: {}", url, e);
+                continue;
+            }
+        };
+
+        match rpc_client.get_block_dag_info().await {
+            Ok(dag_info) => views.push(NodeView {
+                url,
+                rpc_client,
+                virtual_daa_score: dag_info.virtual_daa_score,
+                pruning_point: dag_info.pruning_point_hash,
+                sink: dag_info.sink,
+            }),
+            Err(e) => {
+                warn!("get_block_dag_info failed for {}: {}", url, e);
+                let _ = rpc_client.disconnect().await;
+            }
+        }
+    }
+
+    if views.len() < 2 {
+        warn!("Fewer than 2 RPC nodes reachable, nothing to compare");
+    }
+
+    let mut events = Vec::<SplitEvent>::new();
+    if let Some(reference) = views.iter().max_by_key(|view| view.virtual_daa_score) {
+        for view in views.iter().filter(|view| view.url != reference.url) {
+            let event = |kind: SplitKind, detail: String| SplitEvent {
+                kind,
+                reference_url: reference.url.clone(),
+                node_url: view.url.clone(),
+                reference_daa_score: reference.virtual_daa_score,
+                node_daa_score: view.virtual_daa_score,
+                detail,
+            };
+
+            let daa_lag = reference.virtual_daa_score - view.virtual_daa_score;
+            if daa_lag > config.monitor.max_daa_lag {
+                events.push(event(
+                    SplitKind::Lagging,
+                    format!("{} DAA scores behind", daa_lag),
+                ));
+                // A lagging node is expected to differ in pruning point and sink
+                continue;
+            }
+
+            if view.pruning_point != reference.pruning_point {
+                events.push(event(
+                    SplitKind::PruningPointMismatch,
+                    format!(
+                        "pruning point {} vs {}",
+                        view.pruning_point, reference.pruning_point
+                    ),
+                ));
+            }
+
+            match reference.rpc_client.get_block(view.sink, false).await {
+                Ok(_) => {}
+                Err(e) if is_block_not_found(&e) => events.push(event(
+                    SplitKind::UnknownSink,
+                    format!("sink {} not found on reference node", view.sink),
+                )),
+                Err(e) => warn!(
+                    "Skipping sink check of {}, get_block failed on {}: {}",
+                    view.url, reference.url, e
+                ),
+            }
+        }
+    }
+
+    for view in views {
+        let _ = view.rpc_client.disconnect().await;
+    }
+
+    if events.is_empty() {
+        info!("No consensus divergence detected");
+        return;
+    }
+
+    for event in events.iter() {
+        warn!("{}", event);
+    }
+
+    // Alert first, so a PG failure doesn't swallow the alert
+    crate::utils::alert::send(
+        config,
+        format!("{} | kaspalytics-rs consensus split alert", config.env),
+        events
+            .iter()
+            .map(|event| event.to_string())
+            .collect::<Vec<String>>()
+            .join("\n"),
+    )
+    .await;
+
+    for event in events.iter() {
+        if let Err(e) = event.save(pool).await {
+            error!("Failed to save split event {}: {}", event, e);
+        }
+    }
+}
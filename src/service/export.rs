@@ -0,0 +1,142 @@
+use crate::utils::config::Config;
+use kaspa_consensus::consensus::storage::ConsensusStorage;
+use kaspa_consensus::model::stores::acceptance_data::AcceptanceDataStoreReader;
+use kaspa_consensus::model::stores::block_transactions::BlockTransactionsStoreReader;
+use kaspa_consensus::model::stores::headers::HeaderStoreReader;
+use log::{error, info};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+// Upper bound on export window size, in milliseconds
+const MAX_EXPORT_WINDOW: u64 = 7 * 86_400_000;
+
+const CSV_HEADER: &str = "transaction_id,block_hash,accepting_block_hash,block_time,input_count,output_count,output_amount,fee";
+
+pub struct TransactionExport {
+    storage: Arc<ConsensusStorage>,
+    window_start_time: u64,
+    window_end_time: u64,
+}
+
+// Checks the export window is ordered and no larger than MAX_EXPORT_WINDOW
+pub fn check_window(start_time: u64, end_time: u64) -> Result<(), String> {
+    if end_time < start_time {
+        return Err(String::from("end_time must not be before start_time"));
+    }
+
+    if end_time - start_time > MAX_EXPORT_WINDOW {
+        return Err(format!(
+            "export window cannot exceed {} days",
+            MAX_EXPORT_WINDOW / 86_400_000
+        ));
+    }
+
+    Ok(())
+}
+
+impl TransactionExport {
+    // Window must already be checked with check_window
+    pub fn new(storage: Arc<ConsensusStorage>, start_time: u64, end_time: u64) -> Self {
+        Self {
+            storage,
+            window_start_time: start_time,
+            window_end_time: end_time,
+        }
+    }
+
+    // Streams every transaction accepted by a chain block in the window to `output` as CSV
+    // Fee is left empty for coinbase transactions and when an input cannot be resolved
+    // Returns count of rows written
+    pub fn run(&self, output: &Path) -> Result<u64, Box<dyn Error>> {
+        let chain_blocks = crate::kaspad::db::get_chain_blocks_in_window(
+            &self.storage,
+            self.window_start_time,
+            self.window_end_time,
+        );
+
+        let mut writer = BufWriter::new(File::create(output)?);
+        writeln!(writer, "{}", CSV_HEADER)?;
+
+        let mut rows = 0u64;
+        for accepting_block_hash in chain_blocks.values() {
+            let acceptances = self
+                .storage
+                .acceptance_data_store
+                .get(*accepting_block_hash)?;
+            let utxos =
+                crate::kaspad::db::get_utxos_for_chain_block(&self.storage, *accepting_block_hash)?;
+
+            for mergeset_data in acceptances.iter() {
+                let header = self
+                    .storage
+                    .headers_store
+                    .get_header(mergeset_data.block_hash)?;
+                let transactions = self
+                    .storage
+                    .block_transactions_store
+                    .get(mergeset_data.block_hash)?;
+
+                for accepted in mergeset_data.accepted_transactions.iter() {
+                    let tx = &transactions[accepted.index_within_block as usize];
+                    let output_amount: u64 = tx.outputs.iter().map(|output| output.value).sum();
+
+                    let input_amount = tx
+                        .inputs
+                        .iter()
+                        .map(|input| utxos.get(&input.previous_outpoint).map(|utxo| utxo.amount))
+                        .sum::<Option<u64>>();
+                    let fee = match input_amount {
+                        Some(input_amount) if !tx.is_coinbase() => {
+                            (input_amount - output_amount).to_string()
+                        }
+                        _ => String::new(),
+                    };
+
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{},{},{},{}",
+                        tx.id(),
+                        mergeset_data.block_hash,
+                        accepting_block_hash,
+                        header.timestamp,
+                        tx.inputs.len(),
+                        tx.outputs.len(),
+                        output_amount,
+                        fee
+                    )?;
+                    rows += 1;
+                }
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(rows)
+    }
+
+    // Window must already be checked with check_window
+    // Exits with an error status when the export fails, i.e. the output file can't be written
+    pub fn main(config: Config, start_time: u64, end_time: u64, output: &Path) {
+        let storage = crate::kaspad::db::init_consensus_storage(
+            config.network_id,
+            &config.kaspad_dirs.active_consensus_db_dir,
+        );
+
+        let export = TransactionExport::new(storage, start_time, end_time);
+
+        match export.run(output) {
+            Ok(rows) => info!(
+                "{} accepted transactions exported to {}",
+                rows,
+                output.display()
+            ),
+            Err(e) => {
+                error!("Export to {} failed: {}", output.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
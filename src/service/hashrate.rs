@@ -0,0 +1,158 @@
+use crate::service::stats::Stats;
+use crate::utils::granularity::{Aggregatable, Granularity};
+use chrono::DateTime;
+use sqlx::PgPool;
+
+// Window size passed to the node's estimate_network_hashes_per_second
+pub const RPC_ESTIMATE_WINDOW_SIZE: u32 = 1000;
+
+// Hashes per second between the first and last of `chain_blocks`, given as (timestamp ms, blue work)
+// None when there are fewer than two chain blocks, or no time passed between them
+pub fn blue_work_hashrate(chain_blocks: &[(u64, u128)]) -> Option<f64> {
+    let (first_time, first_work) = chain_blocks.first()?;
+    let (last_time, last_work) = chain_blocks.last()?;
+    if last_time <= first_time {
+        return None;
+    }
+
+    Some((last_work - first_work) as f64 / ((last_time - first_time) as f64 / 1000.0))
+}
+
+#[derive(Debug)]
+pub struct HashrateEstimate {
+    pub epoch_second: u64,
+    pub granularity: Granularity,
+    pub block_count: u64,
+
+    // Hashes per second, from work (header bits) of blocks mined in the period
+    pub estimated_hashrate: f64,
+
+    // Mean work (header bits) per block mined in the period
+    pub mean_block_work: f64,
+
+    // Hashes per second, from blue work delta and timestamp delta of first and last chain block of the period
+    pub blue_work_hashrate: Option<f64>,
+
+    // Hashes per second, as estimated by the node at the last chain block of the period
+    pub rpc_hashrate: Option<f64>,
+}
+
+impl HashrateEstimate {
+    pub fn from_stats(stats: &Stats) -> Self {
+        let granularity = stats.granularity();
+
+        Self {
+            epoch_second: stats.epoch_second,
+            granularity,
+            block_count: stats.transaction_count_per_block.len() as u64,
+            estimated_hashrate: stats.block_work as f64 / granularity.seconds() as f64,
+            mean_block_work: match stats.transaction_count_per_block.len() {
+                0 => 0.0,
+                block_count => stats.block_work as f64 / block_count as f64,
+            },
+            blue_work_hashrate: None,
+            rpc_hashrate: None,
+        }
+    }
+
+    // Relative difference of the header derived estimate from the RPC derived estimate
+    pub fn divergence(&self) -> Option<f64> {
+        self.rpc_hashrate
+            .filter(|rpc_hashrate| *rpc_hashrate > 0.0)
+            .map(|rpc_hashrate| (self.estimated_hashrate - rpc_hashrate) / rpc_hashrate)
+    }
+
+    // Relative difference of the blue work derived estimate from the RPC derived estimate
+    pub fn blue_work_divergence(&self) -> Option<f64> {
+        let blue_work_hashrate = self.blue_work_hashrate?;
+        self.rpc_hashrate
+            .filter(|rpc_hashrate| *rpc_hashrate > 0.0)
+            .map(|rpc_hashrate| (blue_work_hashrate - rpc_hashrate) / rpc_hashrate)
+    }
+
+    // Share by which the estimated hashrate fell below `previous`, negative when it rose
+    pub fn drop_from(&self, previous: f64) -> Option<f64> {
+        match previous > 0.0 {
+            true => Some((previous - self.estimated_hashrate) / previous),
+            false => None,
+        }
+    }
+
+    // Estimated hashrate of the period right before this one, when it was saved
+    pub async fn load_previous_hashrate(&self, pool: &PgPool) -> Option<f64> {
+        let sql = r#"
+            SELECT estimated_hashrate
+            FROM hashrate_summary
+            WHERE timestamp = $1 AND granularity = $2
+        "#;
+
+        let previous = self.epoch_second - self.granularity.seconds();
+        let row: Option<(Option<f64>,)> = sqlx::query_as(sql)
+            .bind(DateTime::from_timestamp(previous as i64, 0).unwrap())
+            .bind(self.granularity.to_string())
+            .fetch_optional(pool)
+            .await
+            .unwrap();
+
+        row.and_then(|(hashrate,)| hashrate)
+    }
+
+    pub async fn save(&self, pool: &PgPool) {
+        let sql = r#"
+            INSERT INTO hashrate_summary
+            (
+                timestamp, granularity, block_count, estimated_hashrate, rpc_hashrate, divergence,
+                mean_block_work, blue_work_hashrate, blue_work_divergence
+            )
+            VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (timestamp, granularity) DO UPDATE SET
+                block_count = EXCLUDED.block_count,
+                estimated_hashrate = EXCLUDED.estimated_hashrate,
+                rpc_hashrate = EXCLUDED.rpc_hashrate,
+                divergence = EXCLUDED.divergence,
+                mean_block_work = EXCLUDED.mean_block_work,
+                blue_work_hashrate = EXCLUDED.blue_work_hashrate,
+                blue_work_divergence = EXCLUDED.blue_work_divergence
+        "#;
+
+        sqlx::query(sql)
+            .bind(DateTime::from_timestamp(self.epoch_second as i64, 0).unwrap())
+            .bind(self.granularity.to_string())
+            .bind(self.block_count as i64)
+            .bind(self.estimated_hashrate)
+            .bind(self.rpc_hashrate)
+            .bind(self.divergence())
+            .bind(self.mean_block_work)
+            .bind(self.blue_work_hashrate)
+            .bind(self.blue_work_divergence())
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashrateEstimate;
+    use crate::utils::granularity::Granularity;
+
+    fn estimate(estimated_hashrate: f64) -> HashrateEstimate {
+        HashrateEstimate {
+            epoch_second: 1_719_792_000,
+            granularity: Granularity::Day,
+            block_count: 864_000,
+            estimated_hashrate,
+            mean_block_work: 0.0,
+            blue_work_hashrate: None,
+            rpc_hashrate: None,
+        }
+    }
+
+    #[test]
+    fn drop_from_previous() {
+        assert_eq!(estimate(75.0).drop_from(100.0), Some(0.25));
+        assert_eq!(estimate(125.0).drop_from(100.0), Some(-0.25));
+        assert_eq!(estimate(100.0).drop_from(0.0), None);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::service::stats::{Counter, Stats};
+use crate::utils::granularity::{Aggregatable, Granularity};
+use chrono::DateTime;
+use kaspa_consensus_core::tx::Transaction;
+use sqlx::PgPool;
+use strum_macros::Display;
+
+// Outputs below 1 KAS are counted as small outputs
+pub const SMALL_OUTPUT_SOMPI: u64 = 100_000_000;
+
+// Marker pushed in the envelope of Kasplex (KRC-20) reveal transactions' signature scripts
+const KASPLEX_MARKER: &[u8] = b"kasplex";
+
+// Share of inscription or repeated payload transactions from which a period is classified
+const INSCRIPTION_SHARE: f64 = 0.5;
+const MIXED_SHARE: f64 = 0.2;
+
+// Share of small outputs from which an otherwise organic period is classified as mixed
+const MIXED_SMALL_OUTPUT_SHARE: f64 = 0.5;
+
+pub fn is_inscription(tx: &Transaction) -> bool {
+    tx.inputs.iter().any(|input| {
+        input
+            .signature_script
+            .windows(KASPLEX_MARKER.len())
+            .any(|window| window == KASPLEX_MARKER)
+    })
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum LoadClass {
+    Organic,
+    Mixed,
+    Inscription,
+}
+
+#[derive(Debug)]
+pub struct LoadClassification {
+    pub epoch_second: u64,
+    pub granularity: Granularity,
+    pub regular_tx_count: u64,
+    pub payload_tx_count: u64,
+    pub repeated_payload_tx_count: u64,
+    pub inscription_tx_count: u64,
+    pub small_output_count: u64,
+    pub output_count: u64,
+    pub class: LoadClass,
+}
+
+fn share(part: u64, total: u64) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => part as f64 / total as f64,
+    }
+}
+
+impl LoadClassification {
+    pub fn from_stats(stats: &Stats) -> Self {
+        let regular_tx_count = stats[Counter::RegularTxCount];
+        let output_count = stats[Counter::OutputCountRegularTx];
+
+        // Inscription storms show up either as Kasplex reveals or as bots repeating one payload
+        let spam_share = share(stats[Counter::InscriptionTxCount], regular_tx_count).max(share(
+            stats[Counter::RepeatedPayloadTxCount],
+            regular_tx_count,
+        ));
+        let small_output_share = share(stats[Counter::SmallOutputCount], output_count);
+
+        let class = if spam_share >= INSCRIPTION_SHARE {
+            LoadClass::Inscription
+        } else if spam_share >= MIXED_SHARE || small_output_share >= MIXED_SMALL_OUTPUT_SHARE {
+            LoadClass::Mixed
+        } else {
+            LoadClass::Organic
+        };
+
+        Self {
+            epoch_second: stats.epoch_second,
+            granularity: stats.granularity(),
+            regular_tx_count,
+            payload_tx_count: stats[Counter::PayloadTxCount],
+            repeated_payload_tx_count: stats[Counter::RepeatedPayloadTxCount],
+            inscription_tx_count: stats[Counter::InscriptionTxCount],
+            small_output_count: stats[Counter::SmallOutputCount],
+            output_count,
+            class,
+        }
+    }
+
+    pub async fn save(&self, pool: &PgPool) {
+        let sql = r#"
+            INSERT INTO load_classification
+            (
+                timestamp, granularity, regular_tx_qty, payload_tx_qty, repeated_payload_tx_qty,
+                inscription_tx_qty, small_output_qty, output_qty, classification
+            )
+            VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (timestamp, granularity) DO UPDATE SET
+                regular_tx_qty = EXCLUDED.regular_tx_qty,
+                payload_tx_qty = EXCLUDED.payload_tx_qty,
+                repeated_payload_tx_qty = EXCLUDED.repeated_payload_tx_qty,
+                inscription_tx_qty = EXCLUDED.inscription_tx_qty,
+                small_output_qty = EXCLUDED.small_output_qty,
+                output_qty = EXCLUDED.output_qty,
+                classification = EXCLUDED.classification
+        "#;
+
+        sqlx::query(sql)
+            .bind(DateTime::from_timestamp(self.epoch_second as i64, 0).unwrap())
+            .bind(self.granularity.to_string())
+            .bind(self.regular_tx_count as i64)
+            .bind(self.payload_tx_count as i64)
+            .bind(self.repeated_payload_tx_count as i64)
+            .bind(self.inscription_tx_count as i64)
+            .bind(self.small_output_count as i64)
+            .bind(self.output_count as i64)
+            .bind(self.class.to_string())
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_inscription, LoadClass, LoadClassification};
+    use crate::service::stats::{Counter, Stats};
+    use crate::utils::granularity::Granularity;
+    use kaspa_consensus_core::subnets::SUBNETWORK_ID_NATIVE;
+    use kaspa_consensus_core::tx::{Transaction, TransactionInput, TransactionOutpoint};
+    use kaspa_consensus_core::Hash;
+
+    fn transaction(signature_scripts: &[&[u8]], payload: &[u8]) -> Transaction {
+        let inputs = signature_scripts
+            .iter()
+            .enumerate()
+            .map(|(i, script)| {
+                TransactionInput::new(
+                    TransactionOutpoint::new(Hash::default(), i as u32),
+                    script.to_vec(),
+                    0,
+                    1,
+                )
+            })
+            .collect();
+
+        Transaction::new(
+            0,
+            inputs,
+            vec![],
+            0,
+            SUBNETWORK_ID_NATIVE,
+            0,
+            payload.to_vec(),
+        )
+    }
+
+    // Hour of 100 regular transactions with 100 outputs
+    fn classify(counters: &[(Counter, u64)]) -> LoadClass {
+        let mut stats = Stats::new(1_719_792_000, Granularity::Hour);
+        stats[Counter::RegularTxCount] = 100;
+        stats[Counter::OutputCountRegularTx] = 100;
+        for (counter, value) in counters {
+            stats[*counter] = *value;
+        }
+
+        LoadClassification::from_stats(&stats).class
+    }
+
+    #[test]
+    fn detects_kasplex_marker() {
+        assert!(is_inscription(&transaction(
+            &[b"\x20sig", b"\x00\x63\x07kasplex\x00"],
+            b""
+        )));
+    }
+
+    #[test]
+    fn ignores_transactions_without_marker() {
+        assert!(!is_inscription(&transaction(&[], b"")));
+        assert!(!is_inscription(&transaction(&[b"\x20sig"], b"")));
+        // Marker split across inputs, or only in the payload, is not an envelope
+        assert!(!is_inscription(&transaction(&[b"kasp", b"lex"], b"")));
+        assert!(!is_inscription(&transaction(&[b"\x20sig"], b"kasplex")));
+    }
+
+    #[test]
+    fn inscription_share_boundary() {
+        assert_eq!(
+            classify(&[(Counter::InscriptionTxCount, 50)]),
+            LoadClass::Inscription
+        );
+        assert_eq!(
+            classify(&[(Counter::InscriptionTxCount, 49)]),
+            LoadClass::Mixed
+        );
+        assert_eq!(
+            classify(&[(Counter::RepeatedPayloadTxCount, 50)]),
+            LoadClass::Inscription
+        );
+    }
+
+    #[test]
+    fn mixed_share_boundary() {
+        assert_eq!(
+            classify(&[(Counter::RepeatedPayloadTxCount, 20)]),
+            LoadClass::Mixed
+        );
+        assert_eq!(
+            classify(&[(Counter::RepeatedPayloadTxCount, 19)]),
+            LoadClass::Organic
+        );
+    }
+
+    #[test]
+    fn small_output_share_boundary() {
+        assert_eq!(
+            classify(&[(Counter::SmallOutputCount, 50)]),
+            LoadClass::Mixed
+        );
+        assert_eq!(
+            classify(&[(Counter::SmallOutputCount, 49)]),
+            LoadClass::Organic
+        );
+    }
+
+    #[test]
+    fn empty_period_is_organic() {
+        assert_eq!(
+            classify(&[
+                (Counter::RegularTxCount, 0),
+                (Counter::OutputCountRegularTx, 0)
+            ]),
+            LoadClass::Organic
+        );
+    }
+}
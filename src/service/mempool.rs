@@ -0,0 +1,91 @@
+use crate::utils::config::Config;
+use crate::utils::granularity::Granularity;
+use chrono::{DateTime, Utc};
+use kaspa_rpc_core::api::rpc::RpcApi;
+use log::info;
+use sqlx::PgPool;
+
+// Lower bounds of feerate bands, in sompi per gram of mass
+const FEERATE_BANDS: [f64; 8] = [0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Band {
+    tx_count: u64,
+    mass_total: u64,
+    fees_total: u64,
+}
+
+// Histogram of the node's mempool (orphans excluded) by feerate band
+#[derive(Debug)]
+pub struct MempoolHistogram {
+    snapshot_at: DateTime<Utc>,
+    bands: [Band; FEERATE_BANDS.len()],
+}
+
+impl MempoolHistogram {
+    pub async fn snapshot(config: &Config) -> Self {
+        let rpc_client = crate::kaspad::rpc::connect(config).await;
+        let entries = rpc_client.get_mempool_entries(false, false).await.unwrap();
+        rpc_client.disconnect().await.unwrap();
+
+        let mut bands = [Band::default(); FEERATE_BANDS.len()];
+        for entry in entries.iter() {
+            let mass = entry.transaction.mass;
+            let feerate = entry.fee as f64 / mass.max(1) as f64;
+
+            // Last band whose lower bound the feerate reaches
+            let index = FEERATE_BANDS
+                .iter()
+                .rposition(|lower| feerate >= *lower)
+                .unwrap_or(0);
+
+            bands[index].tx_count += 1;
+            bands[index].mass_total += mass;
+            bands[index].fees_total += entry.fee;
+        }
+
+        info!("{} mempool transactions snapshotted", entries.len());
+
+        Self {
+            snapshot_at: Utc::now(),
+            bands,
+        }
+    }
+
+    // Saves the snapshot as the histogram of its hour, replacing an earlier snapshot of the same hour
+    pub async fn save(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO mempool_histogram_hourly
+            (hour, feerate_band, tx_count, mass_total, fees_total, snapshot_at)
+            VALUES
+            ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (hour, feerate_band) DO UPDATE SET
+                tx_count = EXCLUDED.tx_count,
+                mass_total = EXCLUDED.mass_total,
+                fees_total = EXCLUDED.fees_total,
+                snapshot_at = EXCLUDED.snapshot_at
+        "#;
+
+        let hour = Granularity::Hour.truncate(self.snapshot_at.timestamp() as u64);
+
+        let mut tx = pool.begin().await?;
+        for (lower, band) in FEERATE_BANDS.iter().zip(self.bands.iter()) {
+            sqlx::query(sql)
+                .bind(DateTime::from_timestamp(hour as i64, 0).unwrap())
+                .bind(*lower)
+                .bind(band.tx_count as i64)
+                .bind(band.mass_total as i64)
+                .bind(band.fees_total as i64)
+                .bind(self.snapshot_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+}
+
+pub async fn run(config: &Config, pool: &PgPool) {
+    let histogram = MempoolHistogram::snapshot(config).await;
+    info!("{:?}", histogram);
+    histogram.save(pool).await.unwrap();
+}
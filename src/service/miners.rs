@@ -0,0 +1,174 @@
+use crate::service::hashrate::HashrateEstimate;
+use crate::service::stats::Stats;
+use chrono::{DateTime, NaiveDate};
+use log::warn;
+use regex::Regex;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+// Pool name of blocks whose miner tag is missing or not matched by the registry
+pub const UNKNOWN_POOL: &str = "unknown";
+
+// Known miner tag patterns, maintained in the PG miner_tag table
+pub struct MinerTagRegistry {
+    patterns: Vec<(Regex, String)>,
+}
+
+impl MinerTagRegistry {
+    pub async fn load(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT pattern, pool_name FROM miner_tag ORDER BY id")
+                .fetch_all(pool)
+                .await?;
+
+        let patterns = rows
+            .into_iter()
+            .filter_map(|(pattern, pool_name)| match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, pool_name)),
+                Err(e) => {
+                    warn!("Skipping invalid miner_tag pattern {}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Self { patterns })
+    }
+
+    // Pool name of the first pattern matching `tag`, in registry order
+    pub fn pool_name(&self, tag: &str) -> Option<&str> {
+        self.patterns
+            .iter()
+            .find(|(regex, _)| regex.is_match(tag))
+            .map(|(_, pool_name)| pool_name.as_str())
+    }
+
+    // Block count per pool name, out of `block_count` blocks
+    // Blocks without a matched miner tag are counted under UNKNOWN_POOL
+    pub fn block_count_per_pool(
+        &self,
+        block_count: u64,
+        block_count_per_miner_tag: &HashMap<String, u64>,
+    ) -> HashMap<String, u64> {
+        let mut block_count_per_pool = HashMap::<String, u64>::new();
+        let mut attributed = 0u64;
+
+        for (tag, count) in block_count_per_miner_tag.iter() {
+            if let Some(pool_name) = self.pool_name(tag) {
+                *block_count_per_pool
+                    .entry(pool_name.to_string())
+                    .or_insert(0) += count;
+                attributed += count;
+            }
+        }
+
+        if block_count > attributed {
+            *block_count_per_pool
+                .entry(UNKNOWN_POOL.to_string())
+                .or_insert(0) += block_count - attributed;
+        }
+
+        block_count_per_pool
+    }
+
+    // Saves block count, block share and estimated hashrate share per pool for `stats` period
+    pub async fn save_pool_blocks(&self, pool: &PgPool, stats: &Stats) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO pool_blocks
+            (timestamp, granularity, pool_name, block_count, block_share, estimated_hashrate)
+            VALUES
+            ($1, $2, $3, $4, $5, $6)
+        "#;
+
+        let hashrate = HashrateEstimate::from_stats(stats);
+        if hashrate.block_count == 0 {
+            return Ok(());
+        }
+
+        let timestamp = DateTime::from_timestamp(hashrate.epoch_second as i64, 0).unwrap();
+        let granularity = hashrate.granularity.to_string();
+
+        // Rows of a previous run of the period are replaced as a whole,
+        // so pools no longer attributed, i.e. after a miner_tag registry change, are dropped
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM pool_blocks WHERE timestamp = $1 AND granularity = $2")
+            .bind(timestamp)
+            .bind(&granularity)
+            .execute(&mut *tx)
+            .await?;
+
+        for (pool_name, count) in self
+            .block_count_per_pool(hashrate.block_count, &stats.block_count_per_miner_tag)
+            .iter()
+        {
+            let block_share = *count as f64 / hashrate.block_count as f64;
+
+            sqlx::query(sql)
+                .bind(timestamp)
+                .bind(&granularity)
+                .bind(pool_name)
+                .bind(*count as i64)
+                .bind(block_share)
+                .bind(block_share * hashrate.estimated_hashrate)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Records tags not matched by any pattern in miner_tag_unmatched for review
+    // Counts are kept per day in miner_tag_unmatched_daily, so re-analysing a day replaces its count
+    pub async fn save_unmatched(
+        &self,
+        pool: &PgPool,
+        date: NaiveDate,
+        block_count_per_miner_tag: &HashMap<String, u64>,
+    ) -> Result<u64, sqlx::Error> {
+        let daily_sql = r#"
+            INSERT INTO miner_tag_unmatched_daily
+            (tag, date, block_count)
+            VALUES
+            ($1, $2, $3)
+            ON CONFLICT (tag, date) DO UPDATE SET
+                block_count = EXCLUDED.block_count
+        "#;
+
+        let sql = r#"
+            INSERT INTO miner_tag_unmatched
+            (tag, first_seen, last_seen, block_count)
+            SELECT $1, $2, $2, SUM(block_count)
+            FROM miner_tag_unmatched_daily
+            WHERE tag = $1
+            ON CONFLICT (tag) DO UPDATE SET
+                first_seen = LEAST(miner_tag_unmatched.first_seen, EXCLUDED.first_seen),
+                last_seen = GREATEST(miner_tag_unmatched.last_seen, EXCLUDED.last_seen),
+                block_count = EXCLUDED.block_count
+        "#;
+
+        let mut unmatched = 0u64;
+        for (tag, count) in block_count_per_miner_tag.iter() {
+            if self.pool_name(tag).is_some() {
+                continue;
+            }
+
+            let mut tx = pool.begin().await?;
+            sqlx::query(daily_sql)
+                .bind(tag)
+                .bind(date)
+                .bind(*count as i64)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(sql)
+                .bind(tag)
+                .bind(date)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            unmatched += 1;
+        }
+
+        Ok(unmatched)
+    }
+}
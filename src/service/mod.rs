@@ -1,22 +1,18 @@
+pub mod address_report;
 pub mod analysis;
+pub mod annotations;
+mod anomaly;
+pub mod changefeed;
+pub mod checksum;
+pub mod consensus;
+pub mod export;
+mod hashrate;
+mod load;
+pub mod mempool;
+mod miners;
+pub mod probe;
 mod stats;
-
-#[allow(dead_code)]
-#[derive(Clone, Copy, Debug)]
-pub enum Granularity {
-    Second,
-    Minute,
-    Hour,
-    Day,
-}
-
-impl std::fmt::Display for Granularity {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Granularity::Second => write!(f, "Second"),
-            Granularity::Minute => write!(f, "Minute"),
-            Granularity::Hour => write!(f, "Hour"),
-            Granularity::Day => write!(f, "Day"),
-        }
-    }
-}
+pub mod totals;
+mod utxo;
+mod velocity;
+mod versions;
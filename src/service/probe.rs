@@ -0,0 +1,124 @@
+use crate::utils::config::Config;
+use kaspa_consensus_core::network::NetworkId;
+use kaspa_rpc_core::api::rpc::RpcApi;
+use kaspa_wrpc_client::client::{ConnectOptions, ConnectStrategy};
+use kaspa_wrpc_client::{KaspaRpcClient, WrpcEncoding};
+use log::{info, warn};
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct ProbeResult {
+    rpc_url: String,
+    reachable: bool,
+    connect_ms: Option<i32>,
+    latency_ms: Option<i32>,
+    is_synced: Option<bool>,
+    virtual_daa_score: Option<i64>,
+}
+
+impl ProbeResult {
+    fn unreachable(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            reachable: false,
+            connect_ms: None,
+            latency_ms: None,
+            is_synced: None,
+            virtual_daa_score: None,
+        }
+    }
+
+    async fn save(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO node_latency
+            (rpc_url, reachable, connect_ms, latency_ms, is_synced, virtual_daa_score)
+            VALUES
+            ($1, $2, $3, $4, $5, $6)
+        "#;
+
+        sqlx::query(sql)
+            .bind(&self.rpc_url)
+            .bind(self.reachable)
+            .bind(self.connect_ms)
+            .bind(self.latency_ms)
+            .bind(self.is_synced)
+            .bind(self.virtual_daa_score)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Connects to a single node and times a get_server_info round trip
+// Failures are recorded as unreachable rather than raised, a public node being down is a valid result
+async fn probe(rpc_url: &str, network_id: NetworkId) -> ProbeResult {
+    let rpc_client = match KaspaRpcClient::new(
+        WrpcEncoding::Borsh,
+        Some(rpc_url),
+        None,
+        Some(network_id),
+        None,
+    ) {
+        Ok(rpc_client) => rpc_client,
+        Err(e) => {
+            warn!("Invalid probe RPC url {}: {}", rpc_url, e);
+            return ProbeResult::unreachable(rpc_url);
+        }
+    };
+
+    let options = ConnectOptions {
+        block_async_connect: true,
+        strategy: ConnectStrategy::Fallback,
+        connect_timeout: Some(PROBE_TIMEOUT),
+        ..Default::default()
+    };
+
+    let connect_start = Instant::now();
+    if let Err(e) = rpc_client.connect(Some(options)).await {
+        warn!("Failed to connect to {}: {}", rpc_url, e);
+        return ProbeResult::unreachable(rpc_url);
+    }
+    let connect_ms = connect_start.elapsed().as_millis() as i32;
+
+    let request_start = Instant::now();
+    let result = match timeout(PROBE_TIMEOUT, rpc_client.get_server_info()).await {
+        Ok(Ok(server_info)) => ProbeResult {
+            rpc_url: rpc_url.to_string(),
+            reachable: true,
+            connect_ms: Some(connect_ms),
+            latency_ms: Some(request_start.elapsed().as_millis() as i32),
+            is_synced: Some(server_info.is_synced),
+            virtual_daa_score: Some(server_info.virtual_daa_score as i64),
+        },
+        Ok(Err(e)) => {
+            warn!("get_server_info failed for {}: {}", rpc_url, e);
+            ProbeResult::unreachable(rpc_url)
+        }
+        Err(_) => {
+            warn!("get_server_info timed out for {}", rpc_url);
+            ProbeResult::unreachable(rpc_url)
+        }
+    };
+
+    let _ = rpc_client.disconnect().await;
+
+    result
+}
+
+pub async fn run(config: &Config, pool: &PgPool) {
+    if config.rpc.probe_urls.is_empty() {
+        warn!("PROBE_RPC_URLS is empty, no nodes to probe");
+        return;
+    }
+
+    for rpc_url in config.rpc.probe_urls.iter() {
+        let result = probe(rpc_url, config.network_id).await;
+        info!("{:?}", result);
+        result.save(pool).await.unwrap();
+    }
+}
@@ -1,10 +1,104 @@
 use chrono::DateTime;
 use kaspa_addresses::Address;
-use sqlx::PgPool;
-use std::collections::{BTreeMap, HashSet};
+use kaspa_consensus_core::subnets::SubnetworkId;
+use sqlx::{PgConnection, PgPool};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::ops::{Index, IndexMut};
+use strum::{EnumCount, IntoEnumIterator};
+use strum_macros::{Display, EnumCount, EnumIter};
+
+use crate::service::changefeed::{self, Entity, Op};
+use crate::utils::granularity::{Aggregatable, Granularity};
+
+// Summed counters tracked per Stats record
+// Adding a counter only requires a new variant here plus the hook that increments it,
+// rollup and Debug output pick it up automatically
+#[derive(Clone, Copy, Debug, Display, EnumCount, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum Counter {
+    // -----------------------------------
+    // Block Summary
+    SpcBlockCount,
+
+    // -----------------------------------
+    // Transaction Summary
+    // Transactions related stats all include only accepted transactions
+    CoinbaseTxCount,
+    RegularTxCount,
+    InputCount,
+    OutputCountCoinbaseTx,
+    OutputCountRegularTx,
+
+    // Count of inputs that didn't resolve to previous output
+    InputCountMissingPreviousOutpoints,
+
+    // Count of transactions skipped due to an input not resolving to previous output
+    SkippedTxCountCannotResolveInputs,
+
+    // Count of merged blocks whose coinbase payload could not be parsed
+    UnparsedCoinbasePayloadCount,
+
+    // -----------------------------------
+    // Load Classification
+    // Regular transactions with a non-empty payload
+    PayloadTxCount,
+
+    // Regular transactions with the same payload as the previous payload transaction of the merged block
+    RepeatedPayloadTxCount,
+
+    // Regular transactions revealing a Kasplex (KRC-20) inscription
+    InscriptionTxCount,
+
+    // Outputs of regular transactions below load::SMALL_OUTPUT_SOMPI
+    SmallOutputCount,
+}
 
-use super::Granularity;
+// Rows per INSERT when saving per second metrics
+const SECONDS_METRICS_BATCH_SIZE: usize = 5_000;
+
+// Mass above which a transaction is counted as near the standard mass limit (100,000)
+const NEAR_LIMIT_MASS: u64 = 90_000;
+
+// Masses of accepted transactions of a single transaction version
+#[derive(Clone, Default)]
+pub struct TxShape {
+    pub compute_mass: Vec<u64>,
+    pub storage_mass: Vec<u64>,
+}
+
+impl TxShape {
+    pub fn push(&mut self, compute_mass: u64, storage_mass: u64) {
+        self.compute_mass.push(compute_mass);
+        self.storage_mass.push(storage_mass);
+    }
+
+    fn merge(&mut self, other: &TxShape) {
+        self.compute_mass.extend(other.compute_mass.clone());
+        self.storage_mass.extend(other.storage_mass.clone());
+    }
+
+    // Ratio of total storage mass to total compute mass
+    fn storage_compute_ratio(&self) -> f64 {
+        let compute_mass: u64 = self.compute_mass.iter().sum();
+        let storage_mass: u64 = self.storage_mass.iter().sum();
+
+        match compute_mass {
+            0 => 0.0,
+            _ => storage_mass as f64 / compute_mass as f64,
+        }
+    }
+
+    fn near_limit_count(&self) -> u64 {
+        self.compute_mass
+            .iter()
+            .zip(self.storage_mass.iter())
+            .filter(|(compute_mass, storage_mass)| {
+                *compute_mass.max(storage_mass) > NEAR_LIMIT_MASS
+            })
+            .count() as u64
+    }
+}
 
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -15,9 +109,11 @@ pub struct Stats {
     // Timestamp of analysis window
     pub epoch_second: u64,
 
+    // Indexed by Counter, see Index<Counter> impl
+    counters: [u64; Counter::COUNT],
+
     // -----------------------------------
     // Block Summary
-    pub spc_block_count: u64,
     // non_spc_block_count: u64 TODO-FUTURE
     // blue_block_count: u64 TODO-FUTURE
     // red_block_count: u64 TODO-FUTURE
@@ -32,26 +128,35 @@ pub struct Stats {
     // Accepted transactions per block
     pub transaction_count_per_block: Vec<u64>,
 
+    // Sum of work (from header bits) of blocks with a timestamp inside this record
+    pub block_work: u128,
+
+    // Merged blocks per miner tag parsed from coinbase payload extra data
+    pub block_count_per_miner_tag: HashMap<String, u64>,
+
+    // Merged blocks per mining node version parsed from coinbase payload extra data
+    pub block_count_per_node_version: HashMap<String, u64>,
+
     // -----------------------------------
     // Transaction Summary
-    // Transactions related stats all include only accepted transactions
-    pub coinbase_tx_count: u64,
-    pub regular_tx_count: u64,
-    pub input_count: u64,
-    pub output_count_coinbase_tx: u64,
-    pub output_count_regular_tx: u64,
     pub fees: Vec<u64>,
 
+    // Accepted transactions per subnetwork (native, coinbase, other)
+    pub tx_count_per_subnetwork: HashMap<SubnetworkId, u64>,
+
+    // Masses of accepted, fully resolved regular transactions per transaction version
+    pub tx_shape_per_version: HashMap<u16, TxShape>,
+
+    // XOR of IDs of counted transactions, independent of processing order
+    pub tx_id_xor: [u8; 32],
+
+    // Output value of fully resolved regular transactions, excluding outputs back to a sending address (change)
+    pub transfer_volume: u128,
+
     // tps_max is not currently populated on per second records
     // only calculater on higher granularities. stores max tps inside the granularity
     pub tps_max: u64,
 
-    // Count of inputs that didn't resolve to previous output
-    pub input_count_missing_previous_outpoints: u64,
-
-    // Count of transactions skipped due to an input not resolving to previous output
-    pub skipped_tx_count_cannot_resolve_inputs: u64,
-
     pub unique_senders: HashSet<Address>,
     pub unique_recipients: HashSet<Address>,
     pub unique_addresses: HashSet<Address>,
@@ -62,18 +167,18 @@ impl Stats {
         Self {
             granularity,
             epoch_second,
-            spc_block_count: 0,
+            counters: [0; Counter::COUNT],
             transaction_count_per_spc_block: Vec::<u64>::new(),
             transaction_count_per_block: Vec::<u64>::new(),
-            coinbase_tx_count: 0,
-            regular_tx_count: 0,
-            input_count: 0,
-            output_count_coinbase_tx: 0,
-            output_count_regular_tx: 0,
+            block_work: 0,
+            block_count_per_miner_tag: HashMap::<String, u64>::new(),
+            block_count_per_node_version: HashMap::<String, u64>::new(),
             fees: Vec::<u64>::new(),
+            tx_count_per_subnetwork: HashMap::<SubnetworkId, u64>::new(),
+            tx_shape_per_version: HashMap::<u16, TxShape>::new(),
+            tx_id_xor: [0; 32],
+            transfer_volume: 0,
             tps_max: 0,
-            input_count_missing_previous_outpoints: 0,
-            skipped_tx_count_cannot_resolve_inputs: 0,
             unique_senders: HashSet::<Address>::new(),
             unique_recipients: HashSet::<Address>::new(),
             unique_addresses: HashSet::<Address>::new(),
@@ -81,6 +186,34 @@ impl Stats {
     }
 }
 
+impl Index<Counter> for Stats {
+    type Output = u64;
+
+    fn index(&self, counter: Counter) -> &u64 {
+        &self.counters[counter as usize]
+    }
+}
+
+impl IndexMut<Counter> for Stats {
+    fn index_mut(&mut self, counter: Counter) -> &mut u64 {
+        &mut self.counters[counter as usize]
+    }
+}
+
+impl Stats {
+    // Accepted transactions, coinbase and regular
+    pub fn tx_count(&self) -> u64 {
+        self[Counter::CoinbaseTxCount] + self[Counter::RegularTxCount]
+    }
+
+    pub fn xor_tx_id(&mut self, tx_id: &[u8; 32]) {
+        self.tx_id_xor
+            .iter_mut()
+            .zip(tx_id.iter())
+            .for_each(|(a, b)| *a ^= b);
+    }
+}
+
 impl Stats {
     fn vec_stats(&self, values: &[u64]) -> (u64, f64, f64, u64, u64) {
         let sum: u64 = values.iter().sum();
@@ -107,12 +240,7 @@ impl Stats {
     }
 
     fn tps_mean(&self) -> f64 {
-        match self.granularity {
-            Granularity::Second => (self.coinbase_tx_count + self.regular_tx_count) as f64,
-            Granularity::Minute => (self.coinbase_tx_count + self.regular_tx_count) as f64 / 60f64,
-            Granularity::Hour => (self.coinbase_tx_count + self.regular_tx_count) as f64 / 3600f64,
-            Granularity::Day => (self.coinbase_tx_count + self.regular_tx_count) as f64 / 86400f64,
-        }
+        self.tx_count() as f64 / self.granularity.seconds() as f64
     }
 
     // fn tps_median - TODO, requires storing more data I think
@@ -135,85 +263,82 @@ impl Stats {
 }
 
 impl Stats {
-    fn calculate_granularity_epoch(epoch_second: u64, granularity: &Granularity) -> u64 {
-        match granularity {
-            Granularity::Second => epoch_second,
-            Granularity::Minute => (epoch_second / 60) * 60,
-            Granularity::Hour => (epoch_second / 3600) * 3600,
-            Granularity::Day => (epoch_second / 86400) * 86400,
+    // Highest per second transaction count within this record
+    // tps_max is not populated on per second records, so it is derived from the counters
+    fn peak_tps(&self) -> u64 {
+        match self.granularity {
+            Granularity::Second => self.tx_count(),
+            _ => self.tps_max,
         }
     }
+}
+
+impl Aggregatable for Stats {
+    fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    fn rebucket(&mut self, epoch_second: u64, granularity: Granularity) {
+        self.tps_max = self.peak_tps();
+        self.granularity = granularity;
+        self.epoch_second = epoch_second;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for counter in Counter::iter() {
+            self[counter] += other[counter];
+        }
+
+        self.transaction_count_per_spc_block
+            .extend(other.transaction_count_per_spc_block.clone());
+        self.transaction_count_per_block
+            .extend(other.transaction_count_per_block.clone());
+        self.block_work += other.block_work;
+
+        for (tag, count) in other.block_count_per_miner_tag.iter() {
+            *self
+                .block_count_per_miner_tag
+                .entry(tag.clone())
+                .or_insert(0) += count;
+        }
+
+        for (version, count) in other.block_count_per_node_version.iter() {
+            *self
+                .block_count_per_node_version
+                .entry(version.clone())
+                .or_insert(0) += count;
+        }
+
+        self.fees.extend(other.fees.clone());
+
+        self.xor_tx_id(&other.tx_id_xor);
+        self.transfer_volume += other.transfer_volume;
 
-    // "Rolls up" per second stats into target granularity
-    // At this time, `per_second_stats` must be per second.
-    // No other source granularity is supported.
-    pub fn rollup(
-        per_second_stats: &BTreeMap<u64, Stats>,
-        target_granularity: Granularity,
-    ) -> BTreeMap<u64, Stats> {
-        let mut rolled_up: BTreeMap<u64, Stats> = BTreeMap::new();
-
-        for (epoch_second, per_second_stats) in per_second_stats {
-            let key = Self::calculate_granularity_epoch(*epoch_second, &target_granularity);
-
-            rolled_up
-                .entry(key)
-                .and_modify(|new_stats| {
-                    new_stats.spc_block_count += per_second_stats.spc_block_count;
-
-                    new_stats
-                        .transaction_count_per_spc_block
-                        .extend(per_second_stats.transaction_count_per_spc_block.clone());
-                    new_stats
-                        .transaction_count_per_block
-                        .extend(per_second_stats.transaction_count_per_block.clone());
-
-                    new_stats.coinbase_tx_count += per_second_stats.coinbase_tx_count;
-                    new_stats.regular_tx_count += per_second_stats.regular_tx_count;
-                    new_stats.input_count += per_second_stats.input_count;
-                    new_stats.output_count_coinbase_tx += per_second_stats.output_count_coinbase_tx;
-                    new_stats.output_count_regular_tx += per_second_stats.output_count_regular_tx;
-                    new_stats.fees.extend(per_second_stats.fees.clone());
-
-                    if per_second_stats.coinbase_tx_count + per_second_stats.regular_tx_count
-                        > new_stats.tps_max
-                    {
-                        new_stats.tps_max =
-                            per_second_stats.coinbase_tx_count + per_second_stats.regular_tx_count;
-                    }
-
-                    new_stats.input_count_missing_previous_outpoints +=
-                        per_second_stats.input_count_missing_previous_outpoints;
-                    new_stats.skipped_tx_count_cannot_resolve_inputs +=
-                        per_second_stats.skipped_tx_count_cannot_resolve_inputs;
-
-                    new_stats
-                        .unique_senders
-                        .extend(per_second_stats.unique_senders.clone());
-                    new_stats
-                        .unique_recipients
-                        .extend(per_second_stats.unique_recipients.clone());
-                    new_stats
-                        .unique_addresses
-                        .extend(per_second_stats.unique_addresses.clone());
-                })
-                .or_insert_with(|| {
-                    let mut new_stats = per_second_stats.clone();
-                    new_stats.granularity = target_granularity;
-                    new_stats.epoch_second = key;
-                    new_stats.tps_max =
-                        per_second_stats.coinbase_tx_count + per_second_stats.regular_tx_count;
-                    new_stats
-                });
+        for (version, shape) in other.tx_shape_per_version.iter() {
+            self.tx_shape_per_version
+                .entry(*version)
+                .or_default()
+                .merge(shape);
         }
 
-        rolled_up
+        for (subnetwork_id, count) in other.tx_count_per_subnetwork.iter() {
+            *self
+                .tx_count_per_subnetwork
+                .entry(subnetwork_id.clone())
+                .or_insert(0) += count;
+        }
+
+        self.tps_max = self.tps_max.max(other.peak_tps());
+
+        self.unique_senders.extend(other.unique_senders.clone());
+        self.unique_recipients
+            .extend(other.unique_recipients.clone());
+        self.unique_addresses.extend(other.unique_addresses.clone());
     }
 }
 
 impl Stats {
-    async fn save_block_summary(&self, pool: &PgPool) {
-        // TODO make sure date record isn't already in table
+    async fn save_block_summary(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
         let sql = r#"
             INSERT INTO block_summary
             (
@@ -224,6 +349,16 @@ impl Stats {
             )
             VALUES
             ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (date) DO UPDATE SET
+                spc_blocks_total = EXCLUDED.spc_blocks_total,
+                txs_per_accepting_block_mean = EXCLUDED.txs_per_accepting_block_mean,
+                txs_per_accepting_block_median = EXCLUDED.txs_per_accepting_block_median,
+                txs_per_accepting_block_min = EXCLUDED.txs_per_accepting_block_min,
+                txs_per_accepting_block_max = EXCLUDED.txs_per_accepting_block_max,
+                txs_per_block_mean = EXCLUDED.txs_per_block_mean,
+                txs_per_block_median = EXCLUDED.txs_per_block_median,
+                txs_per_block_min = EXCLUDED.txs_per_block_min,
+                txs_per_block_max = EXCLUDED.txs_per_block_max
         "#;
 
         let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
@@ -235,7 +370,7 @@ impl Stats {
 
         sqlx::query(sql)
             .bind(date)
-            .bind(self.spc_block_count as i64)
+            .bind(self[Counter::SpcBlockCount] as i64)
             .bind(tpspc.1)
             .bind(tpspc.2)
             .bind(tpspc.3 as i64)
@@ -244,13 +379,13 @@ impl Stats {
             .bind(tpb.2)
             .bind(tpb.3 as i64)
             .bind(tpb.4 as i64)
-            .execute(pool)
-            .await
-            .unwrap();
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
     }
 
-    async fn save_transaction_summary(&self, pool: &PgPool) {
-        // TODO make sure date record isn't already in table
+    async fn save_transaction_summary(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
         let sql = r#"
             INSERT INTO transaction_summary
             (   
@@ -263,6 +398,24 @@ impl Stats {
             )
             VALUES
             ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT (date) DO UPDATE SET
+                coinbase_tx_qty = EXCLUDED.coinbase_tx_qty,
+                tx_qty = EXCLUDED.tx_qty,
+                input_qty_total = EXCLUDED.input_qty_total,
+                output_qty_total_coinbase = EXCLUDED.output_qty_total_coinbase,
+                output_qty_total = EXCLUDED.output_qty_total,
+                fees_total = EXCLUDED.fees_total,
+                fees_mean = EXCLUDED.fees_mean,
+                fees_median = EXCLUDED.fees_median,
+                fees_min = EXCLUDED.fees_min,
+                fees_max = EXCLUDED.fees_max,
+                skipped_tx_missing_inputs = EXCLUDED.skipped_tx_missing_inputs,
+                inputs_missing_previous_outpoint = EXCLUDED.inputs_missing_previous_outpoint,
+                unique_senders = EXCLUDED.unique_senders,
+                unique_recipients = EXCLUDED.unique_recipients,
+                unique_addresses = EXCLUDED.unique_addresses,
+                tx_per_second_mean = EXCLUDED.tx_per_second_mean,
+                tx_per_second_max = EXCLUDED.tx_per_second_max
         "#;
 
         let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
@@ -273,31 +426,235 @@ impl Stats {
 
         sqlx::query(sql)
             .bind(date)
-            .bind(self.coinbase_tx_count as i64)
-            .bind(self.regular_tx_count as i64)
-            .bind(self.input_count as i64)
-            .bind(self.output_count_coinbase_tx as i64)
-            .bind(self.output_count_regular_tx as i64)
+            .bind(self[Counter::CoinbaseTxCount] as i64)
+            .bind(self[Counter::RegularTxCount] as i64)
+            .bind(self[Counter::InputCount] as i64)
+            .bind(self[Counter::OutputCountCoinbaseTx] as i64)
+            .bind(self[Counter::OutputCountRegularTx] as i64)
             .bind(fees.0 as i64)
             .bind(fees.1)
             .bind(fees.2)
             .bind(fees.3 as i64)
             .bind(fees.4 as i64)
-            .bind(self.skipped_tx_count_cannot_resolve_inputs as i64)
-            .bind(self.input_count_missing_previous_outpoints as i64)
+            .bind(self[Counter::SkippedTxCountCannotResolveInputs] as i64)
+            .bind(self[Counter::InputCountMissingPreviousOutpoints] as i64)
             .bind(self.unique_senders.len() as i64)
             .bind(self.unique_recipients.len() as i64)
             .bind(self.unique_address_count() as i64)
             .bind(tps_mean)
             .bind(self.tps_max as i64)
-            .execute(pool)
-            .await
-            .unwrap();
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_subnetwork_summary(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO subnetwork_summary
+            (date, subnetwork_id, tx_qty)
+            VALUES
+            ($1, $2, $3)
+        "#;
+
+        let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
+            .unwrap()
+            .date_naive();
+
+        // Rows of a previous run of the date are replaced as a whole, so subnetworks no longer seen are dropped
+        sqlx::query("DELETE FROM subnetwork_summary WHERE date = $1")
+            .bind(date)
+            .execute(&mut *conn)
+            .await?;
+
+        for (subnetwork_id, count) in self.tx_count_per_subnetwork.iter() {
+            sqlx::query(sql)
+                .bind(date)
+                .bind(subnetwork_id.to_string())
+                .bind(*count as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn save_tx_shape_summary(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO tx_shape_daily
+            (
+                date, version, tx_qty,
+                compute_mass_mean, compute_mass_median, compute_mass_max,
+                storage_mass_mean, storage_mass_median, storage_mass_max,
+                storage_compute_mass_ratio, near_limit_tx_qty
+            )
+            VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#;
+
+        let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
+            .unwrap()
+            .date_naive();
+
+        // Rows of a previous run of the date are replaced as a whole, so versions no longer seen are dropped
+        sqlx::query("DELETE FROM tx_shape_daily WHERE date = $1")
+            .bind(date)
+            .execute(&mut *conn)
+            .await?;
+
+        for (version, shape) in self.tx_shape_per_version.iter() {
+            let compute_mass = self.vec_stats(&shape.compute_mass);
+            let storage_mass = self.vec_stats(&shape.storage_mass);
+
+            sqlx::query(sql)
+                .bind(date)
+                .bind(*version as i32)
+                .bind(shape.compute_mass.len() as i64)
+                .bind(compute_mass.1)
+                .bind(compute_mass.2)
+                .bind(compute_mass.4 as i64)
+                .bind(storage_mass.1)
+                .bind(storage_mass.2)
+                .bind(storage_mass.4 as i64)
+                .bind(shape.storage_compute_ratio())
+                .bind(shape.near_limit_count() as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Saves the daily transfer volume, replacing a previous run of the date
+    async fn save_transfer_volume(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO transfer_volume_daily
+            (date, transfer_volume)
+            VALUES
+            ($1, $2)
+            ON CONFLICT (date) DO UPDATE SET
+                transfer_volume = EXCLUDED.transfer_volume
+        "#;
+
+        let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
+            .unwrap()
+            .date_naive();
+
+        sqlx::query(sql)
+            .bind(date)
+            .bind(self.transfer_volume as f64)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
     }
 
-    pub async fn save(&self, pool: &PgPool) {
-        self.save_block_summary(pool).await;
-        self.save_transaction_summary(pool).await;
+    // Saves per second metrics, in batches of SECONDS_METRICS_BATCH_SIZE rows
+    // Minute/hour views are rolled up in SQL with date_trunc on timestamp
+    pub async fn save_seconds_metrics(
+        pool: &PgPool,
+        seconds: &[&Stats],
+    ) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO seconds_metrics
+            (timestamp, block_count, spc_block_count, tx_count, regular_tx_count, input_count, output_count, fees_total)
+            SELECT * FROM UNNEST($1::timestamptz[], $2::int[], $3::int[], $4::int[], $5::int[], $6::int[], $7::int[], $8::bigint[])
+            ON CONFLICT (timestamp) DO UPDATE SET
+                block_count = EXCLUDED.block_count,
+                spc_block_count = EXCLUDED.spc_block_count,
+                tx_count = EXCLUDED.tx_count,
+                regular_tx_count = EXCLUDED.regular_tx_count,
+                input_count = EXCLUDED.input_count,
+                output_count = EXCLUDED.output_count,
+                fees_total = EXCLUDED.fees_total
+        "#;
+
+        for batch in seconds.chunks(SECONDS_METRICS_BATCH_SIZE) {
+            sqlx::query(sql)
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| {
+                            DateTime::from_timestamp(stats.epoch_second as i64, 0).unwrap()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats.transaction_count_per_block.len() as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats[Counter::SpcBlockCount] as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats.tx_count() as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats[Counter::RegularTxCount] as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats[Counter::InputCount] as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| {
+                            (stats[Counter::OutputCountCoinbaseTx]
+                                + stats[Counter::OutputCountRegularTx])
+                                as i32
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|stats| stats.fees.iter().sum::<u64>() as i64)
+                        .collect::<Vec<_>>(),
+                )
+                .execute(pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Saves daily summaries in a single PG transaction, so a failed save can be retried as a whole
+    // Rows already saved for the date are replaced, so a day can be re-analysed
+    pub async fn save(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        self.save_block_summary(&mut tx).await?;
+        self.save_transaction_summary(&mut tx).await?;
+        self.save_subnetwork_summary(&mut tx).await?;
+        self.save_tx_shape_summary(&mut tx).await?;
+        self.save_transfer_volume(&mut tx).await?;
+        let replaced = crate::service::totals::add_daily(&mut tx, self).await?;
+
+        // Consumers tell a new day from a re-analysed one by the op
+        let op = match replaced {
+            true => Op::Upsert,
+            false => Op::Insert,
+        };
+        let date = DateTime::from_timestamp(self.epoch_second as i64, 0)
+            .unwrap()
+            .date_naive();
+        changefeed::append(&mut tx, Entity::DailyStats, date.to_string(), op).await?;
+
+        tx.commit().await
     }
 }
 
@@ -307,10 +664,16 @@ impl fmt::Debug for Stats {
         let tpb = self.vec_stats(&self.transaction_count_per_block);
         let fees = self.vec_stats(&self.fees);
 
-        f.debug_struct("Stats")
+        let mut debug = f.debug_struct("Stats");
+        debug
             .field("epoch_second", &self.epoch_second)
-            .field("granularity", &self.granularity)
-            .field("spc_block_count", &self.spc_block_count)
+            .field("granularity", &self.granularity);
+
+        for counter in Counter::iter() {
+            debug.field(&counter.to_string(), &self[counter]);
+        }
+
+        debug
             .field("transaction_count_per_spc_block - mean", &tpspc.1)
             .field("transaction_count_per_spc_block - median", &tpspc.2)
             .field("transaction_count_per_spc_block - min", &tpspc.3)
@@ -321,24 +684,11 @@ impl fmt::Debug for Stats {
             .field("transaction_count_per_block - max", &tpb.4)
             .field("tps - mean", &self.tps_mean())
             .field("tps - max", &self.tps_max)
-            .field("coinbase_tx_count", &self.coinbase_tx_count)
-            .field("regular_tx_count", &self.regular_tx_count)
-            .field("input_count", &self.input_count)
-            .field("output_count_coinbase_tx", &self.output_count_coinbase_tx)
-            .field("output_count_regular_tx", &self.output_count_regular_tx)
             .field("fees - total", &(fees.0 / 100_000_000))
             .field("fees - mean", &(fees.1 / 100_000_000.0))
             .field("fees - median", &(fees.2 / 100_000_000.0))
             .field("fees - min", &(fees.3 as f64 / 100_000_000.0))
             .field("fees - max", &(fees.4 as f64 / 100_000_000.0))
-            .field(
-                "input_count_missing_previous_outpoints",
-                &self.input_count_missing_previous_outpoints,
-            )
-            .field(
-                "skipped_tx_count_cannot_resolve_inputs",
-                &self.skipped_tx_count_cannot_resolve_inputs,
-            )
             .field("unique_senders", &self.unique_sender_count())
             .field("unique_recipients", &self.unique_recipient_count())
             .field("unique_addresses", &self.unique_address_count())
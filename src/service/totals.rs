@@ -0,0 +1,258 @@
+use crate::service::stats::{Counter, Stats};
+use chrono::{DateTime, NaiveDate};
+use sqlx::{PgConnection, PgPool};
+use std::fmt;
+
+// Lifetime network totals over all analysed days
+// Kept up to date by Stats::save, so headline totals don't need to aggregate the daily tables
+#[derive(Debug)]
+pub struct NetworkTotals {
+    pub block_count: i64,
+    pub spc_block_count: i64,
+    pub tx_count: i64,
+    pub fees_total: i64,
+    pub day_count: i64,
+    pub last_date: Option<NaiveDate>,
+}
+
+impl fmt::Display for NetworkTotals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "blocks={} spc_blocks={} txs={} fees={} days={} last_date={}",
+            self.block_count,
+            self.spc_block_count,
+            self.tx_count,
+            self.fees_total,
+            self.day_count,
+            self.last_date.map_or(String::from("-"), |d| d.to_string())
+        )
+    }
+}
+
+impl NetworkTotals {
+    pub async fn load(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let sql = r#"
+            SELECT block_count, spc_block_count, tx_count, fees_total, day_count, last_date
+            FROM network_totals
+            WHERE id = 1
+        "#;
+
+        let (block_count, spc_block_count, tx_count, fees_total, day_count, last_date) =
+            sqlx::query_as(sql).fetch_one(pool).await?;
+
+        Ok(Self {
+            block_count,
+            spc_block_count,
+            tx_count,
+            fees_total,
+            day_count,
+            last_date,
+        })
+    }
+}
+
+// Contribution of one analysed day to the lifetime totals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DailyTotals {
+    pub date: NaiveDate,
+    pub block_count: i64,
+    pub spc_block_count: i64,
+    pub tx_count: i64,
+    pub fees_total: i64,
+}
+
+impl fmt::Display for DailyTotals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.date, self.block_count, self.spc_block_count, self.tx_count, self.fees_total
+        )
+    }
+}
+
+impl DailyTotals {
+    fn from_row(
+        (date, block_count, spc_block_count, tx_count, fees_total): (NaiveDate, i64, i64, i64, i64),
+    ) -> Self {
+        Self {
+            date,
+            block_count,
+            spc_block_count,
+            tx_count,
+            fees_total,
+        }
+    }
+
+    fn from_stats(stats: &Stats) -> Self {
+        Self {
+            date: DateTime::from_timestamp(stats.epoch_second as i64, 0)
+                .unwrap()
+                .date_naive(),
+            block_count: stats.transaction_count_per_block.len() as i64,
+            spc_block_count: stats[Counter::SpcBlockCount] as i64,
+            tx_count: stats.tx_count() as i64,
+            fees_total: stats.fees.iter().sum::<u64>() as i64,
+        }
+    }
+
+    // Change to apply to network_totals when this day replaces its previous contribution, if any
+    // Returns the deltas and the change of day_count
+    fn delta(&self, previous: Option<&DailyTotals>) -> (DailyTotals, i64) {
+        match previous {
+            Some(previous) => (
+                DailyTotals {
+                    date: self.date,
+                    block_count: self.block_count - previous.block_count,
+                    spc_block_count: self.spc_block_count - previous.spc_block_count,
+                    tx_count: self.tx_count - previous.tx_count,
+                    fees_total: self.fees_total - previous.fees_total,
+                },
+                0,
+            ),
+            None => (*self, 1),
+        }
+    }
+
+    // Most recent daily contributions, newest first
+    pub async fn load_latest(pool: &PgPool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        let sql = r#"
+            SELECT date, block_count, spc_block_count, tx_count, fees_total
+            FROM network_totals_daily
+            ORDER BY date DESC
+            LIMIT $1
+        "#;
+
+        let rows = sqlx::query_as(sql).bind(limit).fetch_all(pool).await?;
+
+        Ok(rows.into_iter().map(Self::from_row).collect())
+    }
+}
+
+// Adds one day of stats to the lifetime totals
+// When the day was added before, its previous contribution is replaced instead of counted twice
+// Must run in the same transaction as the daily stats it is derived from
+// Returns whether the day was added before
+pub async fn add_daily(conn: &mut PgConnection, stats: &Stats) -> Result<bool, sqlx::Error> {
+    let daily = DailyTotals::from_stats(stats);
+
+    let previous: Option<(NaiveDate, i64, i64, i64, i64)> = sqlx::query_as(
+        r#"
+            SELECT date, block_count, spc_block_count, tx_count, fees_total
+            FROM network_totals_daily
+            WHERE date = $1
+            FOR UPDATE
+        "#,
+    )
+    .bind(daily.date)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO network_totals_daily
+            (date, block_count, spc_block_count, tx_count, fees_total)
+            VALUES
+            ($1, $2, $3, $4, $5)
+            ON CONFLICT (date) DO UPDATE SET
+                block_count = EXCLUDED.block_count,
+                spc_block_count = EXCLUDED.spc_block_count,
+                tx_count = EXCLUDED.tx_count,
+                fees_total = EXCLUDED.fees_total
+        "#,
+    )
+    .bind(daily.date)
+    .bind(daily.block_count)
+    .bind(daily.spc_block_count)
+    .bind(daily.tx_count)
+    .bind(daily.fees_total)
+    .execute(&mut *conn)
+    .await?;
+
+    let previous = previous.map(DailyTotals::from_row);
+    let (delta, day_count_delta) = daily.delta(previous.as_ref());
+    sqlx::query(
+        r#"
+            UPDATE network_totals SET
+                block_count = block_count + $1,
+                spc_block_count = spc_block_count + $2,
+                tx_count = tx_count + $3,
+                fees_total = fees_total + $4,
+                day_count = day_count + $5,
+                last_date = GREATEST(last_date, $6),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = 1
+        "#,
+    )
+    .bind(delta.block_count)
+    .bind(delta.spc_block_count)
+    .bind(delta.tx_count)
+    .bind(delta.fees_total)
+    .bind(day_count_delta)
+    .bind(daily.date)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(previous.is_some())
+}
+
+// Prints lifetime totals, followed by the most recent daily contributions, one per line:
+// date, blocks, spc blocks, txs, fees
+pub async fn run(pool: &PgPool, days: i64) {
+    println!("{}", NetworkTotals::load(pool).await.unwrap());
+    for daily in DailyTotals::load_latest(pool, days).await.unwrap() {
+        println!("{}", daily);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DailyTotals;
+    use chrono::NaiveDate;
+
+    fn daily(block_count: i64, tx_count: i64, fees_total: i64) -> DailyTotals {
+        DailyTotals {
+            date: NaiveDate::from_ymd_opt(2024, 7, 1).unwrap(),
+            block_count,
+            spc_block_count: block_count / 2,
+            tx_count,
+            fees_total,
+        }
+    }
+
+    #[test]
+    fn first_save_adds_whole_day() {
+        let day = daily(864_000, 1_000, 50);
+        assert_eq!(day.delta(None), (day, 1));
+    }
+
+    #[test]
+    fn re_save_replaces_previous_contribution() {
+        let first = daily(864_000, 1_000, 50);
+        let second = daily(864_010, 990, 60);
+
+        let (delta, day_count_delta) = second.delta(Some(&first));
+        assert_eq!(day_count_delta, 0);
+        assert_eq!(delta.block_count, 10);
+        assert_eq!(delta.spc_block_count, 5);
+        assert_eq!(delta.tx_count, -10);
+        assert_eq!(delta.fees_total, 10);
+
+        // Applying both saves to the totals leaves exactly the second contribution
+        let (first_delta, first_days) = first.delta(None);
+        assert_eq!(first_delta.tx_count + delta.tx_count, second.tx_count);
+        assert_eq!(first_days + day_count_delta, 1);
+    }
+
+    #[test]
+    fn identical_re_save_changes_nothing() {
+        let day = daily(864_000, 1_000, 50);
+        let (delta, day_count_delta) = day.delta(Some(&day));
+        assert_eq!(
+            (delta.block_count, delta.tx_count, delta.fees_total),
+            (0, 0, 0)
+        );
+        assert_eq!(day_count_delta, 0);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::service::changefeed::{self, Entity, Op};
+use crate::service::velocity;
+use crate::utils::config::Config;
+use chrono::NaiveDate;
+use kaspa_addresses::Address;
+use kaspa_consensus::consensus::storage::ConsensusStorage;
+use kaspa_txscript::standard::extract_script_pub_key_address;
+use log::info;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Rich list rows inserted per statement
+const RICH_LIST_BATCH_SIZE: usize = 5_000;
+
+// Top percentiles of addresses by balance, in percent
+const TOP_PERCENTILES: [f64; 5] = [0.01, 0.1, 1.0, 5.0, 10.0];
+
+// Wealth concentration of address balances
+#[derive(Debug)]
+pub struct Concentration {
+    pub address_count: u64,
+
+    // 0 when all addresses hold the same balance, approaching 1 when one address holds everything
+    pub gini: f64,
+
+    // Minimum number of addresses holding more than 50% of the balance
+    pub nakamoto: u64,
+
+    // Sum of squared balance shares, 1/address_count when all equal, 1 when one address holds everything
+    pub herfindahl: f64,
+}
+
+impl Concentration {
+    // `ranked` must be sorted by balance, highest first
+    pub fn from_ranked(ranked: &[(Address, u64)]) -> Option<Self> {
+        let total = ranked
+            .iter()
+            .map(|(_, balance)| *balance as u128)
+            .sum::<u128>();
+        if total == 0 {
+            return None;
+        }
+
+        let n = ranked.len() as f64;
+        let total_f = total as f64;
+
+        // Gini over ascending balances: 2 * sum(i * x_i) / (n * sum(x)) - (n + 1) / n
+        let weighted = ranked
+            .iter()
+            .enumerate()
+            .map(|(rank, (_, balance))| (n - rank as f64) * *balance as f64)
+            .sum::<f64>();
+        let gini = 2.0 * weighted / (n * total_f) - (n + 1.0) / n;
+
+        let mut nakamoto = 0u64;
+        let mut cumulative = 0u128;
+        for (_, balance) in ranked {
+            nakamoto += 1;
+            cumulative += *balance as u128;
+            if cumulative * 2 > total {
+                break;
+            }
+        }
+
+        let herfindahl = ranked
+            .iter()
+            .map(|(_, balance)| (*balance as f64 / total_f).powi(2))
+            .sum::<f64>();
+
+        Some(Self {
+            address_count: ranked.len() as u64,
+            gini,
+            nakamoto,
+            herfindahl,
+        })
+    }
+
+    pub async fn save(&self, pool: &PgPool, date: NaiveDate) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO utxo_concentration
+            (date, address_count, gini, nakamoto, herfindahl)
+            VALUES
+            ($1, $2, $3, $4, $5)
+            ON CONFLICT (date) DO UPDATE SET
+                address_count = EXCLUDED.address_count,
+                gini = EXCLUDED.gini,
+                nakamoto = EXCLUDED.nakamoto,
+                herfindahl = EXCLUDED.herfindahl
+        "#;
+
+        sqlx::query(sql)
+            .bind(date)
+            .bind(self.address_count as i64)
+            .bind(self.gini)
+            .bind(self.nakamoto as i64)
+            .bind(self.herfindahl)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Analysis of the node's current (virtual) UTXO set
+pub struct UtxoAnalysis {
+    config: Config,
+    storage: Arc<ConsensusStorage>,
+}
+
+impl UtxoAnalysis {
+    pub fn new(config: Config, storage: Arc<ConsensusStorage>) -> Self {
+        Self { config, storage }
+    }
+
+    // Balance per address, summed over all UTXOs in the virtual UTXO set, highest balance first
+    // UTXOs with non-standard script public keys are skipped
+    pub fn ranked_balances(&self) -> Vec<(Address, u64)> {
+        let mut balances = HashMap::<Address, u64>::new();
+        let mut utxo_count = 0u64;
+
+        for (_, entry) in self
+            .storage
+            .virtual_stores
+            .read()
+            .utxo_set
+            .iterator()
+            .map(|p| p.unwrap())
+        {
+            utxo_count += 1;
+
+            if let Ok(address) = extract_script_pub_key_address(
+                &entry.script_public_key,
+                self.config.network_id.into(),
+            ) {
+                *balances.entry(address).or_insert(0) += entry.amount;
+            }
+        }
+
+        info!(
+            "{} UTXOs loaded for {} addresses from virtual UTXO set",
+            utxo_count,
+            balances.len()
+        );
+
+        let mut ranked = balances.into_iter().collect::<Vec<(Address, u64)>>();
+        ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+        ranked
+    }
+
+    // Saves the top `size` addresses by balance as the rich list snapshot of `date`, in batches of RICH_LIST_BATCH_SIZE rows
+    // Rank of the address in the immediately previous snapshot is stored alongside for rank change,
+    // NULL when the address was not on that snapshot (new to the list)
+    pub async fn save_rich_list(
+        &self,
+        pool: &PgPool,
+        date: NaiveDate,
+        ranked: &[(Address, u64)],
+        size: usize,
+    ) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO rich_list
+            (date, rank, address, balance, previous_rank)
+            SELECT $1, r.rank, r.address, r.balance, p.rank
+            FROM UNNEST($2::int[], $3::varchar[], $4::bigint[]) AS r(rank, address, balance)
+            LEFT JOIN rich_list p ON p.date = $5 AND p.address = r.address
+        "#;
+
+        // Snapshot is saved as a whole or not at all
+        let mut tx = pool.begin().await?;
+
+        let (previous_date,): (Option<NaiveDate>,) =
+            sqlx::query_as("SELECT MAX(date) FROM rich_list WHERE date < $1")
+                .bind(date)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        // Rows of a previous run of the date are replaced as a whole, so a shorter list leaves no stale ranks
+        sqlx::query("DELETE FROM rich_list WHERE date = $1")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+
+        let top = &ranked[..size.min(ranked.len())];
+        for (i, batch) in top.chunks(RICH_LIST_BATCH_SIZE).enumerate() {
+            let first_rank = i * RICH_LIST_BATCH_SIZE + 1;
+            sqlx::query(sql)
+                .bind(date)
+                .bind(
+                    (0..batch.len())
+                        .map(|offset| (first_rank + offset) as i32)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|(address, _)| address.to_string())
+                        .collect::<Vec<_>>(),
+                )
+                .bind(
+                    batch
+                        .iter()
+                        .map(|(_, balance)| *balance as i64)
+                        .collect::<Vec<_>>(),
+                )
+                .bind(previous_date)
+                .execute(&mut *tx)
+                .await?;
+        }
+        changefeed::append(&mut tx, Entity::RichList, date.to_string(), Op::Upsert).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Saves address count and balance stats of the top TOP_PERCENTILES of addresses by balance
+    pub async fn save_percentiles(
+        &self,
+        pool: &PgPool,
+        date: NaiveDate,
+        ranked: &[(Address, u64)],
+    ) -> Result<(), sqlx::Error> {
+        let sql = r#"
+            INSERT INTO utxo_percentiles
+            (date, percentile, address_count, balance_min, balance_max, balance_mean, balance_total)
+            VALUES
+            ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (date, percentile) DO UPDATE SET
+                address_count = EXCLUDED.address_count,
+                balance_min = EXCLUDED.balance_min,
+                balance_max = EXCLUDED.balance_max,
+                balance_mean = EXCLUDED.balance_mean,
+                balance_total = EXCLUDED.balance_total
+        "#;
+
+        if ranked.is_empty() {
+            return Ok(());
+        }
+
+        for percentile in TOP_PERCENTILES {
+            let count = ((ranked.len() as f64 * percentile / 100.0).ceil() as usize).max(1);
+            let top = &ranked[..count];
+            let total = top
+                .iter()
+                .map(|(_, balance)| *balance as u128)
+                .sum::<u128>();
+
+            sqlx::query(sql)
+                .bind(date)
+                .bind(percentile)
+                .bind(count as i64)
+                .bind(top[count - 1].1 as i64)
+                .bind(top[0].1 as i64)
+                .bind(total as f64 / count as f64)
+                .bind(total as i64)
+                .execute(pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Snapshots the node's current UTXO set as of `date`
+    pub async fn main(config: Config, pool: &PgPool, date: NaiveDate) {
+        let storage = crate::kaspad::db::init_consensus_storage(
+            config.network_id,
+            &config.kaspad_dirs.active_consensus_db_dir,
+        );
+
+        let rich_list_size = config.analysis.rich_list_size;
+        let process = UtxoAnalysis::new(config, storage);
+
+        let ranked = process.ranked_balances();
+        process
+            .save_rich_list(pool, date, &ranked, rich_list_size)
+            .await
+            .unwrap();
+        process.save_percentiles(pool, date, &ranked).await.unwrap();
+        velocity::save_supply(pool, date, &ranked).await.unwrap();
+
+        if let Some(concentration) = Concentration::from_ranked(&ranked) {
+            info!(
+                "Balance concentration for {}: gini {:.4}, nakamoto {}, herfindahl {:.6}",
+                date, concentration.gini, concentration.nakamoto, concentration.herfindahl
+            );
+            concentration.save(pool, date).await.unwrap();
+        }
+
+        info!(
+            "Rich list of top {} addresses saved for {}",
+            rich_list_size, date
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Concentration;
+    use kaspa_addresses::{Address, Prefix, Version};
+
+    fn ranked(balances: &[u64]) -> Vec<(Address, u64)> {
+        balances
+            .iter()
+            .enumerate()
+            .map(|(i, balance)| {
+                (
+                    Address::new(Prefix::Mainnet, Version::PubKey, &[i as u8; 32]),
+                    *balance,
+                )
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn empty_input_has_no_concentration() {
+        assert!(Concentration::from_ranked(&[]).is_none());
+        assert!(Concentration::from_ranked(&ranked(&[0, 0])).is_none());
+    }
+
+    #[test]
+    fn equal_balances() {
+        let concentration = Concentration::from_ranked(&ranked(&[25; 4])).unwrap();
+
+        assert_eq!(concentration.address_count, 4);
+        assert_close(concentration.gini, 0.0);
+        assert_eq!(concentration.nakamoto, 3);
+        assert_close(concentration.herfindahl, 0.25);
+    }
+
+    #[test]
+    fn single_holder() {
+        let concentration = Concentration::from_ranked(&ranked(&[1_000])).unwrap();
+
+        assert_eq!(concentration.address_count, 1);
+        assert_close(concentration.gini, 0.0);
+        assert_eq!(concentration.nakamoto, 1);
+        assert_close(concentration.herfindahl, 1.0);
+    }
+
+    #[test]
+    fn one_address_holds_everything() {
+        let concentration = Concentration::from_ranked(&ranked(&[1_000, 0, 0, 0])).unwrap();
+
+        // Maximum Gini for n addresses is (n - 1) / n
+        assert_close(concentration.gini, 0.75);
+        assert_eq!(concentration.nakamoto, 1);
+        assert_close(concentration.herfindahl, 1.0);
+    }
+
+    #[test]
+    fn nakamoto_needs_more_than_half() {
+        // Top two hold exactly half, so a third address is needed
+        let concentration = Concentration::from_ranked(&ranked(&[30, 20, 20, 15, 15])).unwrap();
+        assert_eq!(concentration.nakamoto, 3);
+    }
+}
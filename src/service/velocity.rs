@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+use kaspa_addresses::Address;
+use log::info;
+use sqlx::PgPool;
+
+// Periods velocity is computed over, as PG date_trunc fields
+const PERIODS: [&str; 2] = ["week", "month"];
+
+// Saves the balance total of standard addresses in the UTXO snapshot of `date` as its circulating supply
+pub async fn save_supply(
+    pool: &PgPool,
+    date: NaiveDate,
+    ranked: &[(Address, u64)],
+) -> Result<(), sqlx::Error> {
+    let sql = r#"
+        INSERT INTO supply_history
+        (date, circulating_supply)
+        VALUES
+        ($1, $2)
+        ON CONFLICT (date) DO UPDATE SET
+            circulating_supply = EXCLUDED.circulating_supply
+    "#;
+
+    let supply = ranked
+        .iter()
+        .map(|(_, balance)| *balance as u128)
+        .sum::<u128>();
+
+    sqlx::query(sql)
+        .bind(date)
+        .bind(supply as i64)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Recomputes velocity of the week and month containing `date`
+// Velocity is the period's transfer volume divided by the latest circulating supply up to the period end
+// Periods without a supply snapshot yet are skipped
+pub async fn update(pool: &PgPool, date: NaiveDate) -> Result<(), sqlx::Error> {
+    let sql = r#"
+        INSERT INTO velocity
+        (period_start, period, day_count, transfer_volume, circulating_supply, velocity)
+        SELECT
+            v.period_start, $2, v.day_count, v.transfer_volume, s.circulating_supply,
+            v.transfer_volume / s.circulating_supply
+        FROM (
+            SELECT
+                date_trunc($2, $1::date)::date AS period_start,
+                COUNT(*) AS day_count,
+                SUM(transfer_volume) AS transfer_volume
+            FROM transfer_volume_daily
+            WHERE date_trunc($2, date) = date_trunc($2, $1::date)
+        ) v
+        CROSS JOIN LATERAL (
+            SELECT circulating_supply FROM supply_history
+            WHERE date < v.period_start + ('1 ' || $2)::interval
+            ORDER BY date DESC
+            LIMIT 1
+        ) s
+        WHERE s.circulating_supply > 0
+        ON CONFLICT (period_start, period) DO UPDATE SET
+            day_count = EXCLUDED.day_count,
+            transfer_volume = EXCLUDED.transfer_volume,
+            circulating_supply = EXCLUDED.circulating_supply,
+            velocity = EXCLUDED.velocity
+    "#;
+
+    for period in PERIODS {
+        let updated = sqlx::query(sql)
+            .bind(date)
+            .bind(period)
+            .execute(pool)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            info!(
+                "No circulating supply up to {} {}, velocity skipped",
+                period, date
+            );
+        }
+    }
+
+    Ok(())
+}
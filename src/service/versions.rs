@@ -0,0 +1,54 @@
+use crate::service::stats::Stats;
+use crate::utils::granularity::Aggregatable;
+use chrono::DateTime;
+use sqlx::PgPool;
+
+// Node version of blocks whose coinbase payload has no parsable version
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+// Saves block count and block share per mining node version for `stats` period
+// Tracks miner upgrade adoption, i.e. ahead of hard forks
+pub async fn save_node_version_shares(pool: &PgPool, stats: &Stats) -> Result<(), sqlx::Error> {
+    let sql = r#"
+        INSERT INTO node_version_share
+        (timestamp, granularity, node_version, block_count, block_share)
+        VALUES
+        ($1, $2, $3, $4, $5)
+    "#;
+
+    let block_count = stats.transaction_count_per_block.len() as u64;
+    if block_count == 0 {
+        return Ok(());
+    }
+
+    let mut block_count_per_version = stats.block_count_per_node_version.clone();
+    let attributed = block_count_per_version.values().sum::<u64>();
+    if block_count > attributed {
+        block_count_per_version.insert(UNKNOWN_VERSION.to_string(), block_count - attributed);
+    }
+
+    let timestamp = DateTime::from_timestamp(stats.epoch_second as i64, 0).unwrap();
+    let granularity = stats.granularity().to_string();
+
+    // Rows of a previous run of the period are replaced as a whole, so versions no longer seen are dropped
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM node_version_share WHERE timestamp = $1 AND granularity = $2")
+        .bind(timestamp)
+        .bind(&granularity)
+        .execute(&mut *tx)
+        .await?;
+
+    for (version, count) in block_count_per_version.iter() {
+        sqlx::query(sql)
+            .bind(timestamp)
+            .bind(&granularity)
+            .bind(version)
+            .bind(*count as i64)
+            .bind(*count as f64 / block_count as f64)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
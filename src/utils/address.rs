@@ -0,0 +1,142 @@
+use kaspa_addresses::{Address, Prefix};
+use kaspa_consensus_core::network::NetworkId;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AddressError {
+    Empty,
+    Invalid(String),
+    WrongNetwork { expected: Prefix, found: Prefix },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::Empty => write!(f, "address is empty"),
+            AddressError::Invalid(reason) => write!(f, "invalid address: {}", reason),
+            AddressError::WrongNetwork { expected, found } => {
+                write!(f, "address is for {} but network is {}", found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+// Parses user supplied address input for the given network
+// Input is trimmed and lowercased, and the network prefix is added when missing
+// i.e. " QPZRY...", "kaspa:qpzry..." and "KASPA:QPZRY..." all parse to "kaspa:qpzry..."
+pub fn parse_address(input: &str, network_id: NetworkId) -> Result<Address, AddressError> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Err(AddressError::Empty);
+    }
+
+    let expected = Prefix::from(network_id);
+    let input = match input.contains(':') {
+        true => input,
+        false => format!("{}:{}", expected, input),
+    };
+
+    let address =
+        Address::try_from(input.as_str()).map_err(|e| AddressError::Invalid(e.to_string()))?;
+
+    if address.prefix != expected {
+        return Err(AddressError::WrongNetwork {
+            expected,
+            found: address.prefix,
+        });
+    }
+
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_address, AddressError};
+    use kaspa_addresses::{Address, Prefix, Version};
+    use kaspa_consensus_core::network::{NetworkId, NetworkType};
+
+    const MAINNET: NetworkId = NetworkId::new(NetworkType::Mainnet);
+
+    fn address(prefix: Prefix) -> Address {
+        Address::new(prefix, Version::PubKey, &[7; 32])
+    }
+
+    #[test]
+    fn parses_canonical_address() {
+        let expected = address(Prefix::Mainnet);
+        assert_eq!(
+            parse_address(&expected.to_string(), MAINNET).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn trims_whitespace() {
+        let expected = address(Prefix::Mainnet);
+        assert_eq!(
+            parse_address(&format!(" \t{}\n", expected), MAINNET).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn lowercases_input() {
+        let expected = address(Prefix::Mainnet);
+        assert_eq!(
+            parse_address(&expected.to_string().to_uppercase(), MAINNET).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn adds_missing_prefix() {
+        let expected = address(Prefix::Mainnet);
+        assert_eq!(
+            parse_address(&expected.payload_to_string().to_uppercase(), MAINNET).unwrap(),
+            expected
+        );
+
+        let testnet = NetworkId::with_suffix(NetworkType::Testnet, 11);
+        let expected = address(Prefix::Testnet);
+        assert_eq!(
+            parse_address(&expected.payload_to_string(), testnet).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert!(matches!(
+            parse_address("", MAINNET),
+            Err(AddressError::Empty)
+        ));
+        assert!(matches!(
+            parse_address("  \n", MAINNET),
+            Err(AddressError::Empty)
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_address() {
+        let mut input = address(Prefix::Mainnet).to_string();
+        input.pop();
+        assert!(matches!(
+            parse_address(&input, MAINNET),
+            Err(AddressError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_testnet_address_on_mainnet() {
+        let input = address(Prefix::Testnet).to_string();
+        assert!(matches!(
+            parse_address(&input, MAINNET),
+            Err(AddressError::WrongNetwork {
+                expected: Prefix::Mainnet,
+                found: Prefix::Testnet,
+            })
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::utils::config::Config;
+use async_trait::async_trait;
+use log::{error, warn};
+use serde_json::json;
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Discord rejects message content longer than this
+const WEBHOOK_CONTENT_LIMIT: usize = 2000;
+
+// Destination alerts are delivered to
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> String;
+
+    async fn send(&self, subject: &str, body: &str) -> Result<(), String>;
+}
+
+// Emails alerts to SMTP_TO
+pub struct EmailSink {
+    config: Config,
+}
+
+#[async_trait]
+impl AlertSink for EmailSink {
+    fn name(&self) -> String {
+        format!("email {}", self.config.smtp.to)
+    }
+
+    async fn send(&self, subject: &str, body: &str) -> Result<(), String> {
+        crate::utils::email::send_email(&self.config, subject.to_string(), body.to_string()).await
+    }
+}
+
+// Posts alerts as JSON to a webhook
+// Payload carries `content` (Discord) and `text` (Slack, Mattermost) alongside `subject` and `body`
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap(),
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> String {
+        // Webhook urls carry their secret in the path, so only the host is shown
+        let host = self
+            .url
+            .split('/')
+            .nth(2)
+            .unwrap_or(self.url.as_str())
+            .to_string();
+        format!("webhook {}", host)
+    }
+
+    async fn send(&self, subject: &str, body: &str) -> Result<(), String> {
+        let text = format!("{}\n{}", subject, body);
+        let payload = json!({
+            "subject": subject,
+            "body": body,
+            "text": text,
+            "content": text.chars().take(WEBHOOK_CONTENT_LIMIT).collect::<String>(),
+        });
+
+        self.client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(payload.to_string())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+// Email, plus one webhook per ALERT_WEBHOOK_URLS entry
+pub fn sinks(config: &Config) -> Vec<Box<dyn AlertSink>> {
+    let mut sinks: Vec<Box<dyn AlertSink>> = vec![Box::new(EmailSink {
+        config: config.clone(),
+    })];
+
+    for url in config.alert.webhook_urls.iter() {
+        sinks.push(Box::new(WebhookSink::new(url.clone())));
+    }
+
+    sinks
+}
+
+// Delivers an alert to every sink
+// A failing sink is logged and does not stop delivery to the others
+pub async fn send(config: &Config, subject: String, body: String) {
+    let sinks = sinks(config);
+
+    let mut delivered = 0;
+    for sink in sinks.iter() {
+        match sink.send(&subject, &body).await {
+            Ok(()) => delivered += 1,
+            Err(e) => warn!(
+                "Alert '{}' not delivered to {}: {}",
+                subject,
+                sink.name(),
+                e
+            ),
+        }
+    }
+
+    if delivered == 0 {
+        error!("Alert '{}' not delivered to any sink: {}", subject, body);
+    }
+}
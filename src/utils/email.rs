@@ -1,18 +1,29 @@
 use crate::utils::config::Config;
 use lettre::{Message, SmtpTransport, Transport};
 
-pub fn send_email(config: &Config, subject: String, body: String) {
+fn try_send_email(config: &Config, subject: String, body: String) -> Result<(), String> {
     let message = Message::builder()
-        .from(config.smtp_from.parse().unwrap())
-        .to(config.smtp_to.parse().unwrap())
+        .from(config.smtp.from.parse().map_err(|e| format!("{}", e))?)
+        .to(config.smtp.to.parse().map_err(|e| format!("{}", e))?)
         .subject(subject)
         .body(body)
-        .unwrap();
+        .map_err(|e| e.to_string())?;
 
-    let mailer = SmtpTransport::starttls_relay(&config.smtp_host)
-        .unwrap()
-        .port(config.smtp_port)
+    let mailer = SmtpTransport::starttls_relay(&config.smtp.host)
+        .map_err(|e| e.to_string())?
+        .port(config.smtp.port)
         .build();
 
-    mailer.send(&message).unwrap();
+    mailer.send(&message).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// SMTP transport is blocking, so it runs on the blocking thread pool
+pub async fn send_email(config: &Config, subject: String, body: String) -> Result<(), String> {
+    let config = config.clone();
+
+    tokio::task::spawn_blocking(move || try_send_email(&config, subject, body))
+        .await
+        .map_err(|e| e.to_string())?
 }
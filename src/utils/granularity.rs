@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Granularity {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    pub fn seconds(&self) -> u64 {
+        match self {
+            Granularity::Second => 1,
+            Granularity::Minute => 60,
+            Granularity::Hour => 3600,
+            Granularity::Day => 86400,
+        }
+    }
+
+    // Start of the granularity bucket that `epoch_second` falls in
+    pub fn truncate(&self, epoch_second: u64) -> u64 {
+        (epoch_second / self.seconds()) * self.seconds()
+    }
+}
+
+impl std::fmt::Display for Granularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Granularity::Second => write!(f, "Second"),
+            Granularity::Minute => write!(f, "Minute"),
+            Granularity::Hour => write!(f, "Hour"),
+            Granularity::Day => write!(f, "Day"),
+        }
+    }
+}
+
+// A time bucketed record that can be "rolled up" into a coarser granularity
+pub trait Aggregatable: Clone {
+    fn granularity(&self) -> Granularity;
+
+    // Re-key a copy of a source record as the first member of a coarser bucket
+    fn rebucket(&mut self, epoch_second: u64, granularity: Granularity);
+
+    // Fold another record of the same bucket into self
+    fn merge(&mut self, other: &Self);
+
+    // Rolls up `source` records into `target` granularity.
+    // Source records may be of any granularity finer than or equal to target,
+    // so rollups compose (Second -> Minute -> Hour -> Day).
+    fn rollup(source: &BTreeMap<u64, Self>, target: Granularity) -> BTreeMap<u64, Self> {
+        let mut rolled_up: BTreeMap<u64, Self> = BTreeMap::new();
+
+        for (epoch_second, record) in source {
+            assert!(
+                record.granularity() <= target,
+                "cannot roll up {} records into {}",
+                record.granularity(),
+                target
+            );
+
+            let key = target.truncate(*epoch_second);
+
+            rolled_up
+                .entry(key)
+                .and_modify(|rolled| rolled.merge(record))
+                .or_insert_with(|| {
+                    let mut rolled = record.clone();
+                    rolled.rebucket(key, target);
+                    rolled
+                });
+        }
+
+        rolled_up
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aggregatable, Granularity};
+    use std::collections::BTreeMap;
+
+    // Minimal record with a summed counter and a max field, mirroring Stats tx counts and tps_max
+    #[derive(Clone, Debug, PartialEq)]
+    struct Record {
+        granularity: Granularity,
+        epoch_second: u64,
+        tx_count: u64,
+        tps_max: u64,
+    }
+
+    impl Record {
+        fn peak_tps(&self) -> u64 {
+            match self.granularity {
+                Granularity::Second => self.tx_count,
+                _ => self.tps_max,
+            }
+        }
+    }
+
+    impl Aggregatable for Record {
+        fn granularity(&self) -> Granularity {
+            self.granularity
+        }
+
+        fn rebucket(&mut self, epoch_second: u64, granularity: Granularity) {
+            self.tps_max = self.peak_tps();
+            self.granularity = granularity;
+            self.epoch_second = epoch_second;
+        }
+
+        fn merge(&mut self, other: &Self) {
+            self.tx_count += other.tx_count;
+            self.tps_max = self.tps_max.max(other.peak_tps());
+        }
+    }
+
+    // 2024-07-01T00:00:00Z
+    const JULY_1: u64 = 1_719_792_000;
+
+    // Per second records spread over two days, with uneven tx counts
+    fn seconds() -> BTreeMap<u64, Record> {
+        (0..2 * 86_400)
+            .step_by(97)
+            .map(|offset| {
+                let epoch_second = JULY_1 + offset;
+                let record = Record {
+                    granularity: Granularity::Second,
+                    epoch_second,
+                    tx_count: offset % 13,
+                    tps_max: 0,
+                };
+                (epoch_second, record)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn composed_rollup_matches_direct_rollup() {
+        let seconds = seconds();
+
+        let minutes = Record::rollup(&seconds, Granularity::Minute);
+        let hours = Record::rollup(&minutes, Granularity::Hour);
+        let composed = Record::rollup(&hours, Granularity::Day);
+        let direct = Record::rollup(&seconds, Granularity::Day);
+
+        assert_eq!(composed, direct);
+        assert_eq!(
+            direct.keys().copied().collect::<Vec<u64>>(),
+            vec![JULY_1, JULY_1 + 86_400]
+        );
+        assert_eq!(
+            direct.values().map(|r| r.tx_count).sum::<u64>(),
+            seconds.values().map(|r| r.tx_count).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn max_fields_merge_by_max() {
+        let seconds: BTreeMap<u64, Record> = [3, 9, 5]
+            .into_iter()
+            .enumerate()
+            .map(|(i, tx_count)| {
+                let epoch_second = JULY_1 + i as u64;
+                let record = Record {
+                    granularity: Granularity::Second,
+                    epoch_second,
+                    tx_count,
+                    tps_max: 0,
+                };
+                (epoch_second, record)
+            })
+            .collect();
+
+        let minute = &Record::rollup(&seconds, Granularity::Minute)[&JULY_1];
+        assert_eq!(minute.tx_count, 17);
+        assert_eq!(minute.tps_max, 9);
+
+        let day = &Record::rollup(
+            &Record::rollup(&seconds, Granularity::Minute),
+            Granularity::Day,
+        )[&JULY_1];
+        assert_eq!(day.tx_count, 17);
+        assert_eq!(day.tps_max, 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot roll up Hour records into Minute")]
+    fn rollup_rejects_finer_target() {
+        let hours = Record::rollup(&seconds(), Granularity::Hour);
+        Record::rollup(&hours, Granularity::Minute);
+    }
+}
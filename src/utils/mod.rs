@@ -1,2 +1,7 @@
+pub mod address;
+pub mod alert;
 pub mod config;
 pub mod email;
+pub mod granularity;
+pub mod retry;
+pub mod time;
@@ -0,0 +1,213 @@
+use log::warn;
+use rand::Rng;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+// Whether a failed attempt is worth retrying
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Retry {
+    Transient,
+    Permanent,
+}
+
+// Exponential backoff with jitter
+// Delay before retry n is initial_delay * 2^n, capped at max_delay,
+// then randomized to between half and all of that value
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+    max_elapsed: Option<Duration>,
+}
+
+impl Backoff {
+    pub fn new(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            max_attempts: None,
+            max_elapsed: None,
+        }
+    }
+
+    // Total attempts, including the first one
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    // No retry is started once this much time has passed since the first attempt
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+
+        delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+// Runs `operation` until it succeeds, `classify` marks its error permanent, or `backoff` is exhausted
+// Returns the last error when giving up
+pub async fn retry<T, E, F, Fut, C>(
+    name: &str,
+    backoff: &Backoff,
+    mut operation: F,
+    classify: C,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: Fn(&E) -> Retry,
+    E: fmt::Debug,
+{
+    let start = Instant::now();
+    let mut attempt = 1;
+
+    loop {
+        let e = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        if classify(&e) == Retry::Permanent {
+            return Err(e);
+        }
+
+        if backoff
+            .max_attempts
+            .is_some_and(|max_attempts| attempt >= max_attempts)
+        {
+            return Err(e);
+        }
+
+        let delay = backoff.delay(attempt - 1);
+        if backoff
+            .max_elapsed
+            .is_some_and(|max_elapsed| start.elapsed() + delay > max_elapsed)
+        {
+            return Err(e);
+        }
+
+        warn!(
+            "{} attempt {} failed with error: {:?}. Retrying in {:?}...",
+            name, attempt, e, delay
+        );
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retry, Backoff, Retry};
+    use std::time::{Duration, Instant};
+
+    // Runs `retry` on an operation that always fails, returns how many times it was called
+    async fn attempts(backoff: &Backoff, classify: fn(&&str) -> Retry) -> u32 {
+        let mut calls = 0;
+        let result: Result<(), &str> = retry(
+            "test",
+            backoff,
+            || {
+                calls += 1;
+                async { Err("failed") }
+            },
+            classify,
+        )
+        .await;
+
+        assert_eq!(result, Err("failed"));
+        calls
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_attempts() {
+        let backoff =
+            Backoff::new(Duration::from_millis(1), Duration::from_millis(2)).with_max_attempts(4);
+
+        assert_eq!(attempts(&backoff, |_| Retry::Transient).await, 4);
+    }
+
+    #[tokio::test]
+    async fn stops_once_max_elapsed_is_used() {
+        let backoff = Backoff::new(Duration::from_millis(20), Duration::from_millis(20))
+            .with_max_elapsed(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let calls = attempts(&backoff, |_| Retry::Transient).await;
+
+        // Delays are 10 to 20ms each, so at least 1 and at most 5 retries fit in 50ms
+        assert!((2..=6).contains(&calls), "{} calls", calls);
+        assert!(start.elapsed() < Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn permanent_error_returns_immediately() {
+        let backoff = Backoff::new(Duration::from_secs(60), Duration::from_secs(60));
+
+        let start = Instant::now();
+        assert_eq!(attempts(&backoff, |_| Retry::Permanent).await, 1);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn succeeds_after_transient_errors() {
+        let backoff = Backoff::new(Duration::from_millis(1), Duration::from_millis(1));
+
+        let mut calls = 0;
+        let result: Result<u32, &str> = retry(
+            "test",
+            &backoff,
+            || {
+                calls += 1;
+                let calls = calls;
+                async move {
+                    match calls {
+                        3 => Ok(calls),
+                        _ => Err("failed"),
+                    }
+                }
+            },
+            |_| Retry::Transient,
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn delay_is_jittered_within_capped_backoff() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(1_000));
+
+        for retry in 0..10 {
+            let capped =
+                Duration::from_millis(100 * 2u64.pow(retry)).min(Duration::from_millis(1_000));
+            for _ in 0..100 {
+                let delay = backoff.delay(retry);
+                assert!(
+                    delay >= capped / 2,
+                    "retry {}: {:?} < {:?} / 2",
+                    retry,
+                    delay,
+                    capped
+                );
+                assert!(
+                    delay <= capped,
+                    "retry {}: {:?} > {:?}",
+                    retry,
+                    delay,
+                    capped
+                );
+            }
+        }
+    }
+}
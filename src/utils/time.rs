@@ -0,0 +1,125 @@
+use chrono::{DateTime, NaiveDate};
+
+// Parses a CLI time argument into unix milliseconds
+// Accepts unix milliseconds, an RFC 3339 timestamp or a YYYY-MM-DD date (start of day, UTC)
+// i.e. "1719792000000", "2024-07-01T00:00:00Z" and "2024-07-01" are the same time
+pub fn parse_time(input: &str) -> Result<u64, String> {
+    parse(input, false)
+}
+
+// Parses a CLI end time argument, same as parse_time except a YYYY-MM-DD date is the
+// last millisecond of that day (UTC), so the named day is included in the range
+// i.e. "2024-07-01" is "2024-07-01T23:59:59.999Z"
+pub fn parse_end_time(input: &str) -> Result<u64, String> {
+    parse(input, true)
+}
+
+fn parse(input: &str, end_of_day: bool) -> Result<u64, String> {
+    let input = input.trim();
+
+    // Out of range millis are rejected here, so callers can safely convert back to DateTime
+    if let Ok(millis) = input.parse::<u64>() {
+        return i64::try_from(millis)
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|_| millis)
+            .ok_or(format!("{} is out of range", input));
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return u64::try_from(time.timestamp_millis())
+            .map_err(|_| format!("{} is before unix epoch", input));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let time = match end_of_day {
+            true => date.and_hms_milli_opt(23, 59, 59, 999),
+            false => date.and_hms_opt(0, 0, 0),
+        };
+        return u64::try_from(time.unwrap().and_utc().timestamp_millis())
+            .map_err(|_| format!("{} is before unix epoch", input));
+    }
+
+    Err(format!(
+        "'{}' is not unix milliseconds, an RFC 3339 timestamp or a YYYY-MM-DD date",
+        input
+    ))
+}
+
+pub fn check_range(start_time: u64, end_time: u64) -> Result<(), String> {
+    if end_time < start_time {
+        return Err(format!(
+            "end time {} is before start time {}",
+            DateTime::from_timestamp_millis(end_time as i64).unwrap(),
+            DateTime::from_timestamp_millis(start_time as i64).unwrap()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_range, parse_end_time, parse_time};
+
+    // 2024-07-01T00:00:00Z
+    const JULY_1: u64 = 1_719_792_000_000;
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    #[test]
+    fn parses_unix_milliseconds() {
+        assert_eq!(parse_time("1719792000000"), Ok(JULY_1));
+        assert_eq!(parse_time(" 0 "), Ok(0));
+        assert_eq!(parse_end_time("1719792000000"), Ok(JULY_1));
+    }
+
+    #[test]
+    fn rejects_out_of_range_milliseconds() {
+        assert!(parse_time("99999999999999999").is_err());
+        assert!(parse_end_time("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn parses_rfc3339() {
+        assert_eq!(parse_time("2024-07-01T00:00:00Z"), Ok(JULY_1));
+        assert_eq!(parse_time("2024-07-01T02:00:00.5+02:00"), Ok(JULY_1 + 500));
+        assert_eq!(parse_end_time("2024-07-01T00:00:00Z"), Ok(JULY_1));
+    }
+
+    #[test]
+    fn parses_date_as_start_of_day() {
+        assert_eq!(parse_time("2024-07-01"), Ok(JULY_1));
+    }
+
+    #[test]
+    fn parses_end_date_as_end_of_day() {
+        assert_eq!(parse_end_time("2024-07-01"), Ok(JULY_1 + DAY_MS - 1));
+
+        // Same start and end date covers that whole day
+        let start = parse_time("2024-07-01").unwrap();
+        let end = parse_end_time("2024-07-01").unwrap();
+        assert_eq!(end - start + 1, DAY_MS);
+    }
+
+    #[test]
+    fn rejects_before_unix_epoch() {
+        assert!(parse_time("1969-12-31T23:59:59Z").is_err());
+        assert!(parse_time("1969-12-31").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        assert!(parse_time("").is_err());
+        assert!(parse_time("-1").is_err());
+        assert!(parse_time("2024-07-01 00:00:00").is_err());
+        assert!(parse_time("07/01/2024").is_err());
+        assert!(parse_end_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn checks_range_order() {
+        assert!(check_range(JULY_1, JULY_1).is_ok());
+        assert!(check_range(JULY_1, JULY_1 + 1).is_ok());
+        assert!(check_range(JULY_1 + 1, JULY_1).is_err());
+    }
+}